@@ -0,0 +1,163 @@
+//! Bridges and articulation points of an undirected graph, Tarjan's low-link DFS.
+//!
+//! `discovered[v]` is the DFS time of v, `low[v]` the earliest time reachable from the
+//! subtree of v using at most one back edge. For a tree edge parent -> child:
+//! - `low[child] > discovered[parent]`: nothing below child reaches above it, the edge is a bridge
+//! - `low[child] >= discovered[parent]`: nothing below child gets past parent,
+//!   so parent is an articulation point, unless it is a DFS root. A root is one
+//!   exactly when it has more than one DFS child.
+//!
+//! The skipped back edge is the edge id we came over, not the parent vertex,
+//! so a pair of parallel edges is correctly never a bridge.
+
+use super::{Graph, GraphError};
+
+// Not discovered yet
+const UNVISITED: usize = usize::MAX;
+
+struct LowLinks {
+    bridges: Vec<usize>,
+    articulation_points: Vec<usize>,
+}
+
+impl<W> Graph<W> {
+    /// Ids of the edges whose removal disconnects their endpoints, ascending.
+    /// Look them up with `edge`.
+    pub fn bridges(&self) -> Result<Vec<usize>, GraphError> {
+        self.low_links().map(|low_links| low_links.bridges)
+    }
+
+    /// Vertices whose removal disconnects the rest of their component, ascending
+    pub fn articulation_points(&self) -> Result<Vec<usize>, GraphError> {
+        self.low_links()
+            .map(|low_links| low_links.articulation_points)
+    }
+
+    fn low_links(&self) -> Result<LowLinks, GraphError> {
+        if self.is_directed() {
+            return Err(GraphError::RequiresUndirected);
+        }
+
+        let nf_vertices = self.nf_vertices();
+        let mut discovered = vec![UNVISITED; nf_vertices];
+        let mut low = vec![UNVISITED; nf_vertices];
+        let mut is_articulation = vec![false; nf_vertices];
+        let mut bridges = Vec::new();
+        let mut time = 0;
+
+        for root in 0..nf_vertices {
+            if discovered[root] != UNVISITED {
+                continue;
+            }
+
+            discovered[root] = time;
+            low[root] = time;
+            time += 1;
+            let mut root_children = 0;
+            // (vertex, edge id we arrived over, index of the next neighbour to look at)
+            let mut stack: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+
+            while let Some(&mut (vertex, parent_edge, ref mut next)) = stack.last_mut() {
+                if let Some(&(neighbor, id)) = self.adjacent(vertex).get(*next) {
+                    *next += 1;
+                    if Some(id) == parent_edge {
+                        continue;
+                    }
+
+                    if discovered[neighbor] == UNVISITED {
+                        discovered[neighbor] = time;
+                        low[neighbor] = time;
+                        time += 1;
+                        stack.push((neighbor, Some(id), 0));
+                    } else {
+                        low[vertex] = low[vertex].min(discovered[neighbor]);
+                    }
+                    continue;
+                }
+
+                // All neighbours done, report back to the parent
+                stack.pop();
+                let Some(&(parent, _, _)) = stack.last() else {
+                    continue;
+                };
+
+                low[parent] = low[parent].min(low[vertex]);
+                if low[vertex] > discovered[parent] {
+                    bridges.push(parent_edge.unwrap());
+                }
+                if parent == root {
+                    root_children += 1;
+                } else if low[vertex] >= discovered[parent] {
+                    is_articulation[parent] = true;
+                }
+            }
+
+            if root_children > 1 {
+                is_articulation[root] = true;
+            }
+        }
+
+        bridges.sort_unstable();
+        Ok(LowLinks {
+            bridges,
+            articulation_points: (0..nf_vertices)
+                .filter(|&vertex| is_articulation[vertex])
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_triangles_joined_by_a_bridge() {
+        // 0 - 1       4 - 5
+        //  \ /         \ /
+        //   2 --------- 3      6
+        let graph = Graph::from_edges(
+            7,
+            false,
+            [
+                (0, 1, ()),
+                (1, 2, ()),
+                (2, 0, ()),
+                (2, 3, ()),
+                (3, 4, ()),
+                (4, 5, ()),
+                (5, 3, ()),
+            ],
+        );
+
+        let bridges = graph.bridges().unwrap();
+        assert_eq!(bridges, vec![3]);
+        assert_eq!((graph.edge(3).from, graph.edge(3).to), (2, 3));
+        assert_eq!(graph.articulation_points(), Ok(vec![2, 3]));
+    }
+
+    #[test]
+    fn path_and_star() {
+        let path = Graph::from_edges(4, false, [(0, 1, ()), (1, 2, ()), (2, 3, ())]);
+        assert_eq!(path.bridges(), Ok(vec![0, 1, 2]));
+        assert_eq!(path.articulation_points(), Ok(vec![1, 2]));
+
+        // The centre is the DFS root here, it only counts because it has several children
+        let star = Graph::from_edges(4, false, [(0, 1, ()), (0, 2, ()), (0, 3, ())]);
+        assert_eq!(star.articulation_points(), Ok(vec![0]));
+    }
+
+    #[test]
+    fn parallel_edges_are_not_bridges() {
+        let graph = Graph::from_edges(3, false, [(0, 1, ()), (0, 1, ()), (1, 2, ())]);
+
+        assert_eq!(graph.bridges(), Ok(vec![2]));
+        assert_eq!(graph.articulation_points(), Ok(vec![1]));
+    }
+
+    #[test]
+    fn directed_graph_is_rejected() {
+        let graph = Graph::from_edges(2, true, [(0, 1, ())]);
+        assert_eq!(graph.bridges(), Err(GraphError::RequiresUndirected));
+    }
+}
@@ -0,0 +1,202 @@
+//! Adjacency-list graph with weighted, directed or undirected edges.
+//!
+//! Edges live in one list and get an id in insertion order, the adjacency lists only hold
+//! `(neighbour, edge id)` pairs. An undirected edge shows up in the lists of both endpoints
+//! under the same id, which lets the algorithms tell parallel edges apart from walking
+//! back over the edge they came from.
+//!
+//! Vertices are dense `usize`s like in `UnionFind`, methods panic on vertices that don't exist.
+//! Unweighted graphs simply use `()` as weight.
+
+pub mod connectivity;
+pub mod mst;
+pub mod shortest_path;
+pub mod traversal;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// The algorithm only makes sense on a directed graph (topological sort)
+    RequiresDirected,
+    /// The algorithm only makes sense on an undirected graph (MST, bridges ...)
+    RequiresUndirected,
+    /// The graph has a cycle, the vertices of one of them in order
+    Cycle(Vec<usize>),
+    /// The edge with this id weighs less than zero, which Dijkstra and A* can't handle
+    NegativeWeight(usize),
+    /// A cycle reachable from the source weighs less than zero, its vertices in order
+    NegativeCycle(Vec<usize>),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::RequiresDirected => write!(f, "graph has to be directed"),
+            GraphError::RequiresUndirected => write!(f, "graph has to be undirected"),
+            GraphError::Cycle(cycle) => write!(f, "graph has a cycle through {:?}", cycle),
+            GraphError::NegativeWeight(id) => write!(f, "edge {} has a negative weight", id),
+            GraphError::NegativeCycle(cycle) => {
+                write!(f, "graph has a negative cycle through {:?}", cycle)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge<W> {
+    pub from: usize,
+    pub to: usize,
+    pub weight: W,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<W = ()> {
+    // Vertex -> (neighbour, edge id)
+    adjacency: Vec<Vec<(usize, usize)>>,
+    edges: Vec<Edge<W>>,
+    directed: bool,
+}
+
+impl<W> Graph<W> {
+    pub fn directed(nf_vertices: usize) -> Self {
+        Self::with_direction(nf_vertices, true)
+    }
+
+    pub fn undirected(nf_vertices: usize) -> Self {
+        Self::with_direction(nf_vertices, false)
+    }
+
+    fn with_direction(nf_vertices: usize, directed: bool) -> Self {
+        Self {
+            adjacency: (0..nf_vertices).map(|_| Vec::new()).collect(),
+            edges: Vec::new(),
+            directed,
+        }
+    }
+
+    /// Builds a graph from `(from, to, weight)` triples
+    pub fn from_edges<I>(nf_vertices: usize, directed: bool, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, W)>,
+    {
+        let mut graph = Self::with_direction(nf_vertices, directed);
+        for (from, to, weight) in edges {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    /// Adds a new vertex without edges and returns it
+    pub fn add_vertex(&mut self) -> usize {
+        self.adjacency.push(Vec::new());
+        self.adjacency.len() - 1
+    }
+
+    /// Adds an edge and returns its id, parallel edges and self loops are allowed
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) -> usize {
+        assert!(
+            from < self.adjacency.len() && to < self.adjacency.len(),
+            "edge ({}, {}) is out of range for {} vertices",
+            from,
+            to,
+            self.adjacency.len()
+        );
+
+        let id = self.edges.len();
+        self.edges.push(Edge { from, to, weight });
+        self.adjacency[from].push((to, id));
+        if !self.directed && from != to {
+            self.adjacency[to].push((from, id));
+        }
+        id
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn nf_vertices(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn nf_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn edge(&self, id: usize) -> &Edge<W> {
+        &self.edges[id]
+    }
+
+    pub fn edges(&self) -> &[Edge<W>] {
+        &self.edges
+    }
+
+    /// Neighbours reachable over one edge, with the weight of that edge, in insertion order
+    pub fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, &W)> + '_ {
+        self.adjacency[vertex]
+            .iter()
+            .map(|&(neighbor, id)| (neighbor, &self.edges[id].weight))
+    }
+
+    /// Number of edges leaving the vertex, for undirected graphs the degree
+    pub fn out_degree(&self, vertex: usize) -> usize {
+        self.adjacency[vertex].len()
+    }
+
+    // (neighbour, edge id) pairs, for the algorithms that need the edge ids
+    pub(crate) fn adjacent(&self, vertex: usize) -> &[(usize, usize)] {
+        &self.adjacency[vertex]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let mut graph = Graph::undirected(3);
+        graph.add_edge(0, 1, 5);
+        graph.add_edge(1, 2, 7);
+        let extra = graph.add_vertex();
+        graph.add_edge(extra, 0, 1);
+
+        assert_eq!(graph.nf_vertices(), 4);
+        assert_eq!(graph.nf_edges(), 3);
+        assert_eq!(
+            graph.neighbors(0).collect::<Vec<_>>(),
+            vec![(1, &5), (3, &1)]
+        );
+        assert_eq!(graph.out_degree(1), 2);
+        assert_eq!(
+            graph.edge(1),
+            &Edge {
+                from: 1,
+                to: 2,
+                weight: 7
+            }
+        );
+    }
+
+    #[test]
+    fn directed_edges_go_one_way() {
+        let graph = Graph::from_edges(3, true, [(0, 1, ()), (1, 2, ())]);
+
+        assert!(graph.is_directed());
+        assert_eq!(
+            graph.neighbors(1).map(|(to, _)| to).collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(graph.out_degree(2), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn edge_out_of_range() {
+        let mut graph = Graph::directed(2);
+        graph.add_edge(0, 2, ());
+    }
+}
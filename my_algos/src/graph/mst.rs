@@ -0,0 +1,107 @@
+//! Kruskal's minimum spanning tree on top of `UnionFind`.
+//!
+//! Edges are taken lightest first and kept whenever they join two different components,
+//! `UnionFind` answers "different components?" in near constant time.
+//! On a disconnected graph the result is a minimum spanning forest.
+
+use super::{Edge, Graph, GraphError};
+use crate::sorting::merge_sort::merge_sort_by;
+use crate::union_find::UnionFind;
+
+impl<W: PartialOrd + Clone> Graph<W> {
+    /// Returns the edges of a minimum spanning forest, lightest first.
+    /// Equal weights keep their insertion order, so the result is deterministic.
+    ///
+    /// Panics if two weights can't be compared, e.g. a NaN weight.
+    pub fn kruskal(&self) -> Result<Vec<Edge<W>>, GraphError> {
+        if self.is_directed() {
+            return Err(GraphError::RequiresUndirected);
+        }
+
+        let mut order: Vec<usize> = (0..self.nf_edges()).collect();
+        merge_sort_by(&mut order, |&a, &b| {
+            self.edge(a)
+                .weight
+                .partial_cmp(&self.edge(b).weight)
+                .expect("edge weights must be comparable")
+        });
+
+        let mut components = UnionFind::new(self.nf_vertices());
+        let mut forest = Vec::with_capacity(self.nf_vertices().saturating_sub(1));
+        for id in order {
+            let edge = self.edge(id);
+            if components.is_connected(edge.from, edge.to) {
+                continue;
+            }
+
+            components.union(edge.from, edge.to);
+            forest.push(edge.clone());
+            if components.get_number_of_disjoint_sets() == 1 {
+                break;
+            }
+        }
+
+        Ok(forest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spanning_tree() {
+        //     1       2
+        //  0 --- 1 ------ 2
+        //  |  \  |        |
+        // 4|  3\ |5       |6
+        //  |    \|        |
+        //  3 --- 4 ------ 5
+        //     7       8
+        let graph = Graph::from_edges(
+            6,
+            false,
+            [
+                (0, 1, 1),
+                (1, 2, 2),
+                (0, 3, 4),
+                (0, 4, 3),
+                (1, 4, 5),
+                (2, 5, 6),
+                (3, 4, 7),
+                (4, 5, 8),
+            ],
+        );
+
+        let tree = graph.kruskal().unwrap();
+        let weights: Vec<i32> = tree.iter().map(|edge| edge.weight).collect();
+        assert_eq!(weights, vec![1, 2, 3, 4, 6]);
+        assert_eq!(weights.iter().sum::<i32>(), 16);
+    }
+
+    #[test]
+    fn spanning_forest_with_float_weights() {
+        let graph = Graph::from_edges(
+            5,
+            false,
+            [
+                (0, 1, 0.5),
+                (1, 2, 0.25),
+                (0, 2, 0.1),
+                (3, 4, 2.0),
+                (3, 4, 1.5),
+            ],
+        );
+
+        let forest = graph.kruskal().unwrap();
+        let pairs: Vec<(usize, usize)> = forest.iter().map(|edge| (edge.from, edge.to)).collect();
+        assert_eq!(pairs, vec![(0, 2), (1, 2), (3, 4)]);
+        assert_eq!(forest[2].weight, 1.5);
+    }
+
+    #[test]
+    fn directed_graph_is_rejected() {
+        let graph = Graph::from_edges(2, true, [(0, 1, 1)]);
+        assert_eq!(graph.kruskal(), Err(GraphError::RequiresUndirected));
+    }
+}
@@ -0,0 +1,405 @@
+//! Single source shortest paths: Dijkstra, Bellman-Ford and A*.
+//!
+//! All of them keep a predecessor per vertex next to its distance, so any path can be
+//! walked back from its target. Dijkstra and A* run on `IndexedPriorityQueue`, a binary heap
+//! with `decrease_key`, so every vertex sits in the queue at most once.
+//!
+//! Weights only need to be `Distance`: copyable, addable and comparable,
+//! floating point weights work as long as there is no NaN.
+
+use super::{Graph, GraphError};
+use crate::heap::indexed_priority_queue::{Handle, IndexedPriorityQueue};
+use std::cmp::Ordering;
+use std::ops::Add;
+
+pub trait Distance: Copy + PartialOrd + Add<Output = Self> {
+    fn zero() -> Self;
+}
+
+macro_rules! impl_distance {
+    ($zero:expr => $($ty:ty),*) => {
+        $(
+            impl Distance for $ty {
+                fn zero() -> Self {
+                    $zero
+                }
+            }
+        )*
+    };
+}
+
+impl_distance!(0 => u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_distance!(0.0 => f32, f64);
+
+/// A path and its total weight
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path<D> {
+    pub distance: D,
+    /// From the source to the target, both included
+    pub vertices: Vec<usize>,
+}
+
+/// Distances from one source to every vertex, `None` for unreachable ones
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<D> {
+    source: usize,
+    distance: Vec<Option<D>>,
+    predecessor: Vec<Option<usize>>,
+}
+
+impl<D: Distance> ShortestPaths<D> {
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    pub fn distance(&self, target: usize) -> Option<D> {
+        self.distance[target]
+    }
+
+    pub fn distances(&self) -> &[Option<D>] {
+        &self.distance
+    }
+
+    pub fn path_to(&self, target: usize) -> Option<Path<D>> {
+        let distance = self.distance[target]?;
+        Some(Path {
+            distance,
+            vertices: walk_back(&self.predecessor, target),
+        })
+    }
+}
+
+// Lets `IndexedPriorityQueue` order distances that are only `PartialOrd`
+#[derive(Clone, Copy, PartialEq)]
+struct Key<D>(D);
+
+impl<D: PartialOrd> Eq for Key<D> {}
+
+impl<D: PartialOrd> PartialOrd for Key<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D: PartialOrd> Ord for Key<D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .partial_cmp(&other.0)
+            .expect("distances must be comparable")
+    }
+}
+
+impl<W: Distance> Graph<W> {
+    /// Dijkstra from `source`, O((V + E) log V).
+    /// Fails with `NegativeWeight` if any edge weighs less than zero.
+    pub fn dijkstra(&self, source: usize) -> Result<ShortestPaths<W>, GraphError> {
+        self.check_non_negative()?;
+
+        let mut paths = ShortestPaths {
+            source,
+            distance: vec![None; self.nf_vertices()],
+            predecessor: vec![None; self.nf_vertices()],
+        };
+        self.best_first(&mut paths, None, |_| W::zero());
+        Ok(paths)
+    }
+
+    /// A* from `source` to `target`, `None` if the target can't be reached.
+    ///
+    /// `heuristic(v)` estimates the distance from v to the target. It must never overestimate,
+    /// otherwise the returned path may not be the shortest. With a heuristic that is also
+    /// consistent no vertex is expanded twice, with `|_| 0` this is plain Dijkstra.
+    pub fn a_star<H>(
+        &self,
+        source: usize,
+        target: usize,
+        heuristic: H,
+    ) -> Result<Option<Path<W>>, GraphError>
+    where
+        H: FnMut(usize) -> W,
+    {
+        self.check_non_negative()?;
+
+        let mut paths = ShortestPaths {
+            source,
+            distance: vec![None; self.nf_vertices()],
+            predecessor: vec![None; self.nf_vertices()],
+        };
+        self.best_first(&mut paths, Some(target), heuristic);
+        Ok(paths.path_to(target))
+    }
+
+    /// Bellman-Ford from `source`, O(V * E). Negative weights are fine, but a negative cycle
+    /// reachable from the source has no shortest paths, its vertices are returned as
+    /// `NegativeCycle`. In an undirected graph a negative edge already is such a cycle.
+    pub fn bellman_ford(&self, source: usize) -> Result<ShortestPaths<W>, GraphError> {
+        let nf_vertices = self.nf_vertices();
+        let mut distance: Vec<Option<W>> = vec![None; nf_vertices];
+        let mut predecessor = vec![None; nf_vertices];
+        distance[source] = Some(W::zero());
+
+        // After V - 1 rounds every shortest path is settled, a change in round V means a cycle
+        for round in 0..nf_vertices {
+            let mut relaxed = None;
+            for vertex in 0..nf_vertices {
+                let Some(base) = distance[vertex] else {
+                    continue;
+                };
+                for &(neighbor, id) in self.adjacent(vertex) {
+                    let candidate = base + self.edge(id).weight;
+                    if distance[neighbor].is_none_or(|current| candidate < current) {
+                        distance[neighbor] = Some(candidate);
+                        predecessor[neighbor] = Some(vertex);
+                        relaxed = Some(neighbor);
+                    }
+                }
+            }
+
+            match relaxed {
+                None => break,
+                Some(vertex) if round == nf_vertices - 1 => {
+                    return Err(GraphError::NegativeCycle(negative_cycle(
+                        &predecessor,
+                        vertex,
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(ShortestPaths {
+            source,
+            distance,
+            predecessor,
+        })
+    }
+
+    fn check_non_negative(&self) -> Result<(), GraphError> {
+        match (0..self.nf_edges()).find(|&id| self.edge(id).weight < W::zero()) {
+            Some(id) => Err(GraphError::NegativeWeight(id)),
+            None => Ok(()),
+        }
+    }
+
+    // Shared by Dijkstra and A*: expand vertices by distance + heuristic,
+    // stop early once `target` is taken out of the queue
+    fn best_first<H>(&self, paths: &mut ShortestPaths<W>, target: Option<usize>, mut heuristic: H)
+    where
+        H: FnMut(usize) -> W,
+    {
+        let mut queue = IndexedPriorityQueue::new();
+        let mut handles: Vec<Option<Handle>> = vec![None; self.nf_vertices()];
+        let source = paths.source;
+        paths.distance[source] = Some(W::zero());
+        handles[source] = Some(queue.push(source, Key(heuristic(source))));
+
+        while let Some((vertex, _)) = queue.pop() {
+            if Some(vertex) == target {
+                return;
+            }
+
+            let base = paths.distance[vertex].unwrap();
+            for &(neighbor, id) in self.adjacent(vertex) {
+                let candidate = base + self.edge(id).weight;
+                if paths.distance[neighbor].is_some_and(|current| candidate >= current) {
+                    continue;
+                }
+
+                paths.distance[neighbor] = Some(candidate);
+                paths.predecessor[neighbor] = Some(vertex);
+                let priority = Key(candidate + heuristic(neighbor));
+                match handles[neighbor] {
+                    Some(handle) if queue.contains(handle) => {
+                        queue.decrease_key(handle, priority).unwrap();
+                    }
+                    // New, or already expanded under an inconsistent heuristic: (re)open it
+                    _ => handles[neighbor] = Some(queue.push(neighbor, priority)),
+                }
+            }
+        }
+    }
+}
+
+fn walk_back(predecessor: &[Option<usize>], target: usize) -> Vec<usize> {
+    let mut vertices = vec![target];
+    let mut vertex = target;
+    while let Some(previous) = predecessor[vertex] {
+        vertices.push(previous);
+        vertex = previous;
+    }
+    vertices.reverse();
+    vertices
+}
+
+// `vertex` was relaxed in the last round, so it is on or behind a negative cycle.
+// Following predecessors V times certainly lands on the cycle, then walk it once.
+fn negative_cycle(predecessor: &[Option<usize>], vertex: usize) -> Vec<usize> {
+    let mut on_cycle = vertex;
+    for _ in 0..predecessor.len() {
+        on_cycle = predecessor[on_cycle].unwrap();
+    }
+
+    let mut cycle = vec![on_cycle];
+    let mut vertex = predecessor[on_cycle].unwrap();
+    while vertex != on_cycle {
+        cycle.push(vertex);
+        vertex = predecessor[vertex].unwrap();
+    }
+    cycle.reverse();
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn road_network() -> Graph<u32> {
+        //      7        9
+        //  0 ----- 1 ------ 4
+        //  |     / |        |
+        // 2|  3/   |10      |1
+        //  |  /    |        |
+        //  2 ----- 3 ------ 5     6
+        //      8        2
+        Graph::from_edges(
+            7,
+            false,
+            [
+                (0, 1, 7),
+                (0, 2, 2),
+                (1, 2, 3),
+                (1, 3, 10),
+                (1, 4, 9),
+                (2, 3, 8),
+                (3, 5, 2),
+                (4, 5, 1),
+            ],
+        )
+    }
+
+    #[test]
+    fn dijkstra_distances_and_paths() {
+        let paths = road_network().dijkstra(0).unwrap();
+
+        assert_eq!(
+            paths.distances(),
+            &[
+                Some(0),
+                Some(5),
+                Some(2),
+                Some(10),
+                Some(13),
+                Some(12),
+                None
+            ]
+        );
+        assert_eq!(
+            paths.path_to(4),
+            Some(Path {
+                distance: 13,
+                vertices: vec![0, 2, 3, 5, 4]
+            })
+        );
+        assert_eq!(paths.path_to(0).unwrap().vertices, vec![0]);
+        assert_eq!(paths.path_to(6), None);
+    }
+
+    #[test]
+    fn dijkstra_rejects_negative_weights() {
+        let graph = Graph::from_edges(3, true, [(0, 1, 4), (1, 2, -1)]);
+        assert_eq!(
+            graph.dijkstra(0).unwrap_err(),
+            GraphError::NegativeWeight(1)
+        );
+    }
+
+    #[test]
+    fn bellman_ford_matches_dijkstra() {
+        let graph = road_network();
+        let expected = graph.dijkstra(0).unwrap();
+        let actual = graph.bellman_ford(0).unwrap();
+
+        assert_eq!(actual.distances(), expected.distances());
+        assert_eq!(actual.path_to(4), expected.path_to(4));
+    }
+
+    #[test]
+    fn bellman_ford_negative_weights() {
+        let graph = Graph::from_edges(
+            4,
+            true,
+            [(0, 1, 4), (0, 2, 5), (2, 1, -3), (1, 3, 2), (3, 2, 1)],
+        );
+
+        let paths = graph.bellman_ford(0).unwrap();
+        assert_eq!(paths.distances(), &[Some(0), Some(2), Some(5), Some(4)]);
+        assert_eq!(paths.path_to(3).unwrap().vertices, vec![0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn bellman_ford_negative_cycle() {
+        // 1 -> 2 -> 3 -> 1 weighs -1 in total
+        let graph = Graph::from_edges(
+            5,
+            true,
+            [(0, 1, 1), (1, 2, 2), (2, 3, -4), (3, 1, 1), (3, 4, 1)],
+        );
+
+        let Err(GraphError::NegativeCycle(mut cycle)) = graph.bellman_ford(0) else {
+            panic!("expected a negative cycle");
+        };
+        let start = cycle.iter().position(|&vertex| vertex == 1).unwrap();
+        cycle.rotate_left(start);
+        assert_eq!(cycle, vec![1, 2, 3]);
+
+        // The cycle can't be reached from 4, so it doesn't matter there
+        assert!(graph.bellman_ford(4).is_ok());
+    }
+
+    #[test]
+    fn a_star_on_a_grid() {
+        // 10x10 grid with a wall in column 5 that is only open in the last row
+        let width = 10;
+        let index = |x: usize, y: usize| y * width + x;
+        let is_wall = |x: usize, y: usize| x == 5 && y < width - 1;
+
+        let mut graph = Graph::undirected(width * width);
+        for y in 0..width {
+            for x in 0..width {
+                if is_wall(x, y) {
+                    continue;
+                }
+                if x + 1 < width && !is_wall(x + 1, y) {
+                    graph.add_edge(index(x, y), index(x + 1, y), 1.0);
+                }
+                if y + 1 < width && !is_wall(x, y + 1) {
+                    graph.add_edge(index(x, y), index(x, y + 1), 1.0);
+                }
+            }
+        }
+
+        let (target_x, target_y) = (9, 0);
+        let manhattan = |vertex: usize| {
+            let (x, y) = (vertex % width, vertex / width);
+            (x.abs_diff(target_x) + y.abs_diff(target_y)) as f64
+        };
+
+        let path = graph
+            .a_star(index(0, 0), index(target_x, target_y), manhattan)
+            .unwrap()
+            .unwrap();
+        // Down to the gap, through it and back up again
+        assert_eq!(path.distance, 9.0 + 9.0 + 9.0);
+        assert_eq!(path.vertices.len(), 28);
+        assert_eq!(
+            Some(path.distance),
+            graph
+                .dijkstra(index(0, 0))
+                .unwrap()
+                .distance(index(target_x, target_y))
+        );
+
+        let unreachable = graph.a_star(index(0, 0), index(5, 0), |_| 0.0).unwrap();
+        assert_eq!(unreachable, None);
+    }
+}
@@ -0,0 +1,312 @@
+//! BFS, DFS, topological sort and cycle detection.
+//!
+//! Everything is iterative with an explicit stack or queue, so a long path graph
+//! cannot overflow the call stack.
+
+use super::{Graph, GraphError};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+// The vertices Kahn's algorithm may emit next, the queue decides which one goes first
+trait ReadyQueue: Default {
+    fn push(&mut self, vertex: usize);
+    fn pop(&mut self) -> Option<usize>;
+}
+
+impl ReadyQueue for VecDeque<usize> {
+    fn push(&mut self, vertex: usize) {
+        self.push_back(vertex);
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.pop_front()
+    }
+}
+
+impl ReadyQueue for BinaryHeap<Reverse<usize>> {
+    fn push(&mut self, vertex: usize) {
+        BinaryHeap::push(self, Reverse(vertex));
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        BinaryHeap::pop(self).map(|Reverse(vertex)| vertex)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Unvisited,
+    OnStack,
+    Done,
+}
+
+impl<W> Graph<W> {
+    /// Vertices reachable from `start` in breadth first order
+    pub fn bfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.nf_vertices()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(vertex) = queue.pop_front() {
+            order.push(vertex);
+            for &(neighbor, _) in self.adjacent(vertex) {
+                if !visited[neighbor] {
+                    visited[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Vertices reachable from `start` in depth first preorder,
+    /// the same order a recursive DFS visiting neighbours in insertion order would give
+    pub fn dfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.nf_vertices()];
+        let mut order = vec![start];
+        // (vertex, index of the next neighbour to look at)
+        let mut stack = vec![(start, 0)];
+        visited[start] = true;
+
+        while let Some((vertex, next)) = stack.last_mut() {
+            let Some(&(neighbor, _)) = self.adjacent(*vertex).get(*next) else {
+                stack.pop();
+                continue;
+            };
+
+            *next += 1;
+            if !visited[neighbor] {
+                visited[neighbor] = true;
+                order.push(neighbor);
+                stack.push((neighbor, 0));
+            }
+        }
+
+        order
+    }
+
+    /// Kahn's algorithm, O(V + E). Vertices are emitted in the order they become ready,
+    /// starting with the sources by index.
+    /// A cyclic graph has no topological order, one of its cycles is returned instead.
+    pub fn topological_sort(&self) -> Result<Vec<usize>, GraphError> {
+        self.kahn::<VecDeque<usize>>()
+    }
+
+    /// Like `topological_sort`, but of all ready vertices the smallest one goes next, so the
+    /// result is the lexicographically smallest topological order. O((V + E) log V).
+    pub fn lexicographic_topological_sort(&self) -> Result<Vec<usize>, GraphError> {
+        self.kahn::<BinaryHeap<Reverse<usize>>>()
+    }
+
+    fn kahn<Q: ReadyQueue>(&self) -> Result<Vec<usize>, GraphError> {
+        if !self.is_directed() {
+            return Err(GraphError::RequiresDirected);
+        }
+
+        let mut in_degree = vec![0; self.nf_vertices()];
+        for edge in self.edges() {
+            in_degree[edge.to] += 1;
+        }
+
+        let mut ready = Q::default();
+        for vertex in (0..self.nf_vertices()).filter(|&vertex| in_degree[vertex] == 0) {
+            ready.push(vertex);
+        }
+        let mut order = Vec::with_capacity(self.nf_vertices());
+        while let Some(vertex) = ready.pop() {
+            order.push(vertex);
+            for &(neighbor, _) in self.adjacent(vertex) {
+                in_degree[neighbor] -= 1;
+                if in_degree[neighbor] == 0 {
+                    ready.push(neighbor);
+                }
+            }
+        }
+
+        if order.len() < self.nf_vertices() {
+            let cycle = self.find_cycle().expect("vertices left over means a cycle");
+            return Err(GraphError::Cycle(cycle));
+        }
+        Ok(order)
+    }
+
+    /// Returns the vertices of some cycle in order, `None` if the graph has none.
+    /// In an undirected graph walking back over the same edge is not a cycle,
+    /// but two parallel edges or a self loop are.
+    pub fn find_cycle(&self) -> Option<Vec<usize>> {
+        let mut color = vec![Color::Unvisited; self.nf_vertices()];
+
+        for start in 0..self.nf_vertices() {
+            if color[start] != Color::Unvisited {
+                continue;
+            }
+
+            // (vertex, edge id we arrived over, index of the next neighbour to look at)
+            let mut stack: Vec<(usize, Option<usize>, usize)> = vec![(start, None, 0)];
+            color[start] = Color::OnStack;
+
+            while let Some((vertex, parent_edge, next)) = stack.last_mut() {
+                let Some(&(neighbor, id)) = self.adjacent(*vertex).get(*next) else {
+                    color[*vertex] = Color::Done;
+                    stack.pop();
+                    continue;
+                };
+
+                *next += 1;
+                if Some(id) == *parent_edge {
+                    continue;
+                }
+                match color[neighbor] {
+                    Color::Unvisited => {
+                        color[neighbor] = Color::OnStack;
+                        stack.push((neighbor, Some(id), 0));
+                    }
+                    // Back edge: the cycle is everything on the stack from `neighbor` up
+                    Color::OnStack => {
+                        let begin = stack
+                            .iter()
+                            .position(|&(on_stack, _, _)| on_stack == neighbor)
+                            .unwrap();
+                        return Some(
+                            stack[begin..]
+                                .iter()
+                                .map(|&(vertex, _, _)| vertex)
+                                .collect(),
+                        );
+                    }
+                    Color::Done => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.find_cycle().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_cycle<W>(graph: &Graph<W>, cycle: &[usize]) -> bool {
+        (0..cycle.len()).all(|index| {
+            let next = cycle[(index + 1) % cycle.len()];
+            graph
+                .neighbors(cycle[index])
+                .any(|(neighbor, _)| neighbor == next)
+        })
+    }
+
+    #[test]
+    fn bfs_and_dfs_order() {
+        //   0 - 1 - 3
+        //   |   |
+        //   2 - 4   5
+        let graph = Graph::from_edges(
+            6,
+            false,
+            [(0, 1, ()), (0, 2, ()), (1, 3, ()), (1, 4, ()), (2, 4, ())],
+        );
+
+        assert_eq!(graph.bfs(0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(graph.dfs(0), vec![0, 1, 3, 4, 2]);
+        assert_eq!(graph.dfs(5), vec![5]);
+    }
+
+    #[test]
+    fn topological_order() {
+        // Build steps: 0 must run before 1 and 2, both before 3 ...
+        let graph = Graph::from_edges(
+            5,
+            true,
+            [(0, 1, ()), (0, 2, ()), (1, 3, ()), (2, 3, ()), (4, 0, ())],
+        );
+
+        assert_eq!(graph.topological_sort(), Ok(vec![4, 0, 1, 2, 3]));
+        assert!(!graph.has_cycle());
+    }
+
+    #[test]
+    fn lexicographic_topological_order() {
+        // 0 only becomes ready after 2, yet it still goes before 3, which became ready first
+        let graph = Graph::from_edges(4, true, [(2, 0, ()), (1, 3, ())]);
+        assert_eq!(graph.topological_sort(), Ok(vec![1, 2, 3, 0]));
+        assert_eq!(graph.lexicographic_topological_sort(), Ok(vec![1, 2, 0, 3]));
+
+        let graph = Graph::from_edges(
+            6,
+            true,
+            [
+                (5, 2, ()),
+                (5, 0, ()),
+                (4, 0, ()),
+                (4, 1, ()),
+                (2, 3, ()),
+                (3, 1, ()),
+            ],
+        );
+        assert_eq!(
+            graph.lexicographic_topological_sort(),
+            Ok(vec![4, 5, 0, 2, 3, 1])
+        );
+    }
+
+    #[test]
+    fn topological_sort_reports_cycle() {
+        let graph = Graph::from_edges(4, true, [(0, 1, ()), (1, 2, ()), (2, 3, ()), (3, 1, ())]);
+
+        match graph.topological_sort() {
+            Err(GraphError::Cycle(cycle)) => {
+                assert_eq!(cycle.len(), 3);
+                assert!(is_cycle(&graph, &cycle));
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        assert_eq!(
+            Graph::<()>::undirected(2).topological_sort(),
+            Err(GraphError::RequiresDirected)
+        );
+    }
+
+    #[test]
+    fn undirected_cycles() {
+        let tree = Graph::from_edges(4, false, [(0, 1, ()), (1, 2, ()), (1, 3, ())]);
+        assert!(!tree.has_cycle());
+
+        let mut parallel = tree.clone();
+        parallel.add_edge(2, 1, ());
+        assert_eq!(parallel.find_cycle().map(|cycle| cycle.len()), Some(2));
+
+        let mut triangle = tree.clone();
+        triangle.add_edge(3, 0, ());
+        let cycle = triangle.find_cycle().unwrap();
+        assert_eq!(cycle.len(), 3);
+        assert!(is_cycle(&triangle, &cycle));
+
+        let mut self_loop = tree;
+        self_loop.add_edge(3, 3, ());
+        assert_eq!(self_loop.find_cycle(), Some(vec![3]));
+    }
+
+    #[test]
+    fn long_path_does_not_overflow() {
+        let nf_vertices = 200_000;
+        let graph = Graph::from_edges(
+            nf_vertices,
+            true,
+            (1..nf_vertices).map(|vertex| (vertex - 1, vertex, ())),
+        );
+
+        assert_eq!(graph.dfs(0).len(), nf_vertices);
+        assert!(!graph.has_cycle());
+        assert_eq!(
+            graph.topological_sort().unwrap()[nf_vertices - 1],
+            nf_vertices - 1
+        );
+    }
+}
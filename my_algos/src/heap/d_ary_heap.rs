@@ -0,0 +1,215 @@
+//! Array backed heap where every node has `arity` children.
+//!
+//! The children of node `i` live at `arity * i + 1 ..= arity * i + arity`.
+//! A wider heap is shallower, so `push` (sift up) gets cheaper while `pop` (sift down) has to
+//! look at more children per level. 4 is a good default when pushes dominate, e.g. Dijkstra.
+//! The same heap serves as min or max heap depending on its `HeapKind`,
+//! no `Reverse` wrapping needed.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapKind {
+    /// `pop` returns the smallest element
+    Min,
+    /// `pop` returns the largest element
+    Max,
+}
+
+pub struct DAryHeap<T> {
+    data: Vec<T>,
+    arity: usize,
+    kind: HeapKind,
+}
+
+impl<T: Ord> DAryHeap<T> {
+    /// Panics if `arity` is smaller than 2
+    pub fn new(arity: usize, kind: HeapKind) -> Self {
+        assert!(
+            arity >= 2,
+            "a heap needs an arity of at least 2, got {}",
+            arity
+        );
+
+        Self {
+            data: Vec::new(),
+            arity,
+            kind,
+        }
+    }
+
+    pub fn new_min(arity: usize) -> Self {
+        Self::new(arity, HeapKind::Min)
+    }
+
+    pub fn new_max(arity: usize) -> Self {
+        Self::new(arity, HeapKind::Max)
+    }
+
+    /// Builds the heap bottom up in O(n)
+    pub fn from_vec(data: Vec<T>, arity: usize, kind: HeapKind) -> Self {
+        let mut heap = Self::new(arity, kind);
+        heap.data = data;
+
+        let mut is_higher = higher_priority(kind);
+        for node in (0..heap.data.len() / arity + 1).rev() {
+            sift_down_by(&mut heap.data, node, arity, &mut is_higher);
+        }
+
+        heap
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        let last = self.data.len() - 1;
+        sift_up_by(
+            &mut self.data,
+            last,
+            self.arity,
+            &mut higher_priority(self.kind),
+        );
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let top = self.data.swap_remove(0);
+        sift_down_by(
+            &mut self.data,
+            0,
+            self.arity,
+            &mut higher_priority(self.kind),
+        );
+        Some(top)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn kind(&self) -> HeapKind {
+        self.kind
+    }
+
+    /// Pops everything, so the result is in `pop` order
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(value) = self.pop() {
+            sorted.push(value);
+        }
+
+        sorted
+    }
+}
+
+/// Returns whether `a` belongs above `b` in a heap of this kind
+fn higher_priority<T: Ord>(kind: HeapKind) -> impl Fn(&T, &T) -> bool {
+    move |a: &T, b: &T| match kind {
+        HeapKind::Min => a < b,
+        HeapKind::Max => a > b,
+    }
+}
+
+pub(crate) fn sift_up_by<T, F>(data: &mut [T], mut node: usize, arity: usize, is_higher: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    while node > 0 {
+        let parent = (node - 1) / arity;
+        if !is_higher(&data[node], &data[parent]) {
+            return;
+        }
+
+        data.swap(node, parent);
+        node = parent;
+    }
+}
+
+pub(crate) fn sift_down_by<T, F>(data: &mut [T], mut node: usize, arity: usize, is_higher: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let first_child = arity * node + 1;
+        if first_child >= data.len() {
+            return;
+        }
+
+        let last_child = (first_child + arity).min(data.len());
+        let mut best = node;
+        for child in first_child..last_child {
+            if is_higher(&data[child], &data[best]) {
+                best = child;
+            }
+        }
+        if best == node {
+            return;
+        }
+
+        data.swap(node, best);
+        node = best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn min_heap() {
+        let mut heap = DAryHeap::new_min(2);
+        for value in [5, 1, 8, 3, 9, 2] {
+            heap.push(value);
+        }
+
+        assert_eq!(heap.peek(), Some(&1));
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 3, 5, 8, 9]);
+    }
+
+    #[test]
+    fn max_heap_any_arity() {
+        let mut input: Vec<i32> = (0..1000).collect();
+        input.shuffle(&mut thread_rng());
+        let expected: Vec<i32> = (0..1000).rev().collect();
+
+        for arity in [2, 3, 4, 8, 16] {
+            let mut heap = DAryHeap::new_max(arity);
+            for &value in &input {
+                heap.push(value);
+            }
+            assert_eq!(heap.into_sorted_vec(), expected);
+
+            let heap = DAryHeap::from_vec(input.clone(), arity, HeapKind::Max);
+            assert_eq!(heap.into_sorted_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn empty_heap() {
+        let mut heap: DAryHeap<u8> = DAryHeap::new_min(4);
+        assert!(heap.is_empty());
+        assert_eq!(heap.peek(), None);
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn arity_one() {
+        DAryHeap::<u8>::new_min(1);
+    }
+}
@@ -0,0 +1,73 @@
+//! In-place heap sort on top of the d-ary heap sift routines.
+//!
+//! The slice is turned into a max heap, then the root is swapped behind the shrinking heap
+//! until the heap is empty. O(n log n) in every case and no extra memory, but not stable.
+
+use super::d_ary_heap::sift_down_by;
+use std::cmp::Ordering;
+
+// A 4-ary heap is half as deep and friendlier to the cache than a binary one
+const ARITY: usize = 4;
+
+pub fn heap_sort<T: Ord>(input: &mut [T]) {
+    heap_sort_by(input, T::cmp);
+}
+
+pub fn heap_sort_by<T, F>(input: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // Max heap with respect to `compare`, so the largest element ends up last
+    let mut is_higher = |a: &T, b: &T| compare(a, b) == Ordering::Greater;
+    let input_len = input.len();
+
+    for node in (0..input_len / ARITY + 1).rev() {
+        sift_down_by(input, node, ARITY, &mut is_higher);
+    }
+
+    for end in (1..input_len).rev() {
+        input.swap(0, end);
+        sift_down_by(&mut input[..end], 0, ARITY, &mut is_higher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn heap_sort_numbers() {
+        let result: Vec<i32> = (-500..500).collect();
+        let mut input = result.clone();
+        input.shuffle(&mut thread_rng());
+
+        heap_sort(&mut input);
+        assert_eq!(input, result);
+
+        let mut empty: [i32; 0] = [];
+        heap_sort(&mut empty);
+    }
+
+    #[test]
+    fn heap_sort_by_descending_strings() {
+        let mut input = vec!["b".to_string(), "c".to_string(), "a".to_string()];
+        heap_sort_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn heap_sort_by_counting_comparator() {
+        let mut input: Vec<u32> = (0..1000).collect();
+        input.shuffle(&mut thread_rng());
+
+        let mut comparisons = 0;
+        heap_sort_by(&mut input, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(input, (0..1000).collect::<Vec<_>>());
+        assert!(comparisons > 0);
+    }
+}
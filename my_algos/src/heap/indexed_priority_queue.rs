@@ -0,0 +1,383 @@
+//! Min priority queue whose entries can be reprioritised or removed after they were pushed.
+//!
+//! `push` hands out a `Handle`. The queue remembers where every handle sits in the heap,
+//! so `decrease_key`, `increase_key` and `remove` find their entry in O(1)
+//! and only need one O(log n) sift. That is exactly what Dijkstra, Prim or a timer wheel
+//! need, without pushing duplicates and skipping stale entries later.
+//! The slot of a popped or removed entry goes on a free list and is reused by the next `push`,
+//! so memory follows the number of queued entries, not the number of pushes ever made.
+//! Every slot counts its reuses and a handle remembers the count it was made with,
+//! so a handle to a vacated slot stays invalid even after the slot holds a new entry.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Handle {
+    id: usize,
+    generation: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityError {
+    /// The handle was already popped or removed
+    NotInQueue,
+    /// `decrease_key` got a bigger priority or `increase_key` a smaller one
+    WrongDirection,
+}
+
+impl fmt::Display for PriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriorityError::NotInQueue => write!(f, "handle is no longer in the queue"),
+            PriorityError::WrongDirection => {
+                write!(f, "new priority moves the entry the wrong way")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PriorityError {}
+
+struct Entry<V, P> {
+    value: V,
+    priority: P,
+}
+
+pub struct IndexedPriorityQueue<V, P> {
+    // Handle ids in heap order
+    heap: Vec<usize>,
+    // Handle id -> position in `heap`, None once the entry left the queue
+    positions: Vec<Option<usize>>,
+    // Handle id -> entry, None once the entry left the queue
+    entries: Vec<Option<Entry<V, P>>>,
+    // Handle id -> how often the slot was vacated, stale handles carry an older count
+    generations: Vec<usize>,
+    // Vacated handle ids, reused by `push`
+    free: Vec<usize>,
+}
+
+impl<V, P: Ord> Default for IndexedPriorityQueue<V, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, P: Ord> IndexedPriorityQueue<V, P> {
+    pub fn new() -> Self {
+        Self {
+            heap: Vec::new(),
+            positions: Vec::new(),
+            entries: Vec::new(),
+            generations: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_ok()
+    }
+
+    pub fn push(&mut self, value: V, priority: P) -> Handle {
+        let entry = Some(Entry { value, priority });
+        let position = Some(self.heap.len());
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.entries[id] = entry;
+                self.positions[id] = position;
+                id
+            }
+            None => {
+                self.entries.push(entry);
+                self.positions.push(position);
+                self.generations.push(0);
+                self.entries.len() - 1
+            }
+        };
+        self.heap.push(id);
+
+        self.sift_up(self.heap.len() - 1);
+        self.handle(id)
+    }
+
+    /// The entry with the smallest priority
+    pub fn peek(&self) -> Option<(&V, &P)> {
+        self.heap.first().map(|&id| {
+            let entry = self.entry(id);
+            (&entry.value, &entry.priority)
+        })
+    }
+
+    pub fn pop(&mut self) -> Option<(V, P)> {
+        let &id = self.heap.first()?;
+        self.remove(self.handle(id))
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&V> {
+        self.position(handle).ok()?;
+        Some(&self.entry(handle.id).value)
+    }
+
+    pub fn priority(&self, handle: Handle) -> Option<&P> {
+        self.position(handle).ok()?;
+        Some(&self.entry(handle.id).priority)
+    }
+
+    /// Lowers the priority of an entry, moving it towards the front
+    pub fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), PriorityError> {
+        let position = self.position(handle)?;
+        if priority > self.entry(handle.id).priority {
+            return Err(PriorityError::WrongDirection);
+        }
+
+        self.entry_mut(handle.id).priority = priority;
+        self.sift_up(position);
+        Ok(())
+    }
+
+    /// Raises the priority of an entry, moving it towards the back
+    pub fn increase_key(&mut self, handle: Handle, priority: P) -> Result<(), PriorityError> {
+        let position = self.position(handle)?;
+        if priority < self.entry(handle.id).priority {
+            return Err(PriorityError::WrongDirection);
+        }
+
+        self.entry_mut(handle.id).priority = priority;
+        self.sift_down(position);
+        Ok(())
+    }
+
+    /// Sets a new priority no matter in which direction it moves the entry
+    pub fn change_priority(&mut self, handle: Handle, priority: P) -> Result<(), PriorityError> {
+        if priority < *self.priority(handle).ok_or(PriorityError::NotInQueue)? {
+            self.decrease_key(handle, priority)
+        } else {
+            self.increase_key(handle, priority)
+        }
+    }
+
+    /// Takes the entry out of the queue, `None` if it already left
+    pub fn remove(&mut self, handle: Handle) -> Option<(V, P)> {
+        let position = self.position(handle).ok()?;
+        let last = self.heap.len() - 1;
+
+        self.swap(position, last);
+        self.heap.pop();
+        self.positions[handle.id] = None;
+        self.generations[handle.id] += 1;
+        self.free.push(handle.id);
+
+        // The entry moved into the hole can belong either above or below it
+        if position < self.heap.len() {
+            self.sift_up(position);
+            self.sift_down(position);
+        }
+
+        self.entries[handle.id]
+            .take()
+            .map(|entry| (entry.value, entry.priority))
+    }
+
+    fn position(&self, handle: Handle) -> Result<usize, PriorityError> {
+        match self.generations.get(handle.id) {
+            Some(&generation) if generation == handle.generation => {
+                self.positions[handle.id].ok_or(PriorityError::NotInQueue)
+            }
+            _ => Err(PriorityError::NotInQueue),
+        }
+    }
+
+    // The handle for the entry currently in slot `id`
+    fn handle(&self, id: usize) -> Handle {
+        Handle {
+            id,
+            generation: self.generations[id],
+        }
+    }
+
+    // Only called for ids that are still in the heap
+    fn entry(&self, id: usize) -> &Entry<V, P> {
+        self.entries[id].as_ref().unwrap()
+    }
+
+    fn entry_mut(&mut self, id: usize) -> &mut Entry<V, P> {
+        self.entries[id].as_mut().unwrap()
+    }
+
+    fn is_less(&self, a: usize, b: usize) -> bool {
+        self.entry(self.heap[a]).priority < self.entry(self.heap[b]).priority
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+
+    fn sift_up(&mut self, mut node: usize) {
+        while node > 0 {
+            let parent = (node - 1) / 2;
+            if !self.is_less(node, parent) {
+                return;
+            }
+
+            self.swap(node, parent);
+            node = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut node: usize) {
+        loop {
+            let left = 2 * node + 1;
+            let right = left + 1;
+            let mut smallest = node;
+
+            if left < self.heap.len() && self.is_less(left, smallest) {
+                smallest = left;
+            }
+            if right < self.heap.len() && self.is_less(right, smallest) {
+                smallest = right;
+            }
+            if smallest == node {
+                return;
+            }
+
+            self.swap(node, smallest);
+            node = smallest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pops_in_priority_order() {
+        let mut queue = IndexedPriorityQueue::new();
+        for (value, priority) in [("c", 3), ("a", 1), ("d", 4), ("b", 2)] {
+            queue.push(value, priority);
+        }
+
+        assert_eq!(queue.peek(), Some((&"a", &1)));
+        let popped: Vec<&str> =
+            std::iter::from_fn(|| queue.pop().map(|(value, _)| value)).collect();
+        assert_eq!(popped, vec!["a", "b", "c", "d"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn decrease_and_increase_key() {
+        let mut queue = IndexedPriorityQueue::new();
+        let far = queue.push("far", 100);
+        let near = queue.push("near", 1);
+        queue.push("mid", 50);
+
+        queue.decrease_key(far, 0).unwrap();
+        assert_eq!(queue.peek(), Some((&"far", &0)));
+
+        queue.increase_key(far, 75).unwrap();
+        queue.increase_key(near, 60).unwrap();
+        assert_eq!(queue.pop(), Some(("mid", 50)));
+        assert_eq!(queue.pop(), Some(("near", 60)));
+        assert_eq!(queue.pop(), Some(("far", 75)));
+    }
+
+    #[test]
+    fn wrong_direction_and_stale_handles() {
+        let mut queue = IndexedPriorityQueue::new();
+        let handle = queue.push((), 10);
+
+        assert_eq!(
+            queue.decrease_key(handle, 11),
+            Err(PriorityError::WrongDirection)
+        );
+        assert_eq!(
+            queue.increase_key(handle, 9),
+            Err(PriorityError::WrongDirection)
+        );
+
+        queue.pop();
+        assert!(!queue.contains(handle));
+        assert_eq!(
+            queue.decrease_key(handle, 1),
+            Err(PriorityError::NotInQueue)
+        );
+        assert_eq!(queue.remove(handle), None);
+    }
+
+    #[test]
+    fn remove_from_the_middle() {
+        let mut queue = IndexedPriorityQueue::new();
+        let handles: Vec<Handle> = (0..100)
+            .map(|value| queue.push(value, (value * 37) % 100))
+            .collect();
+
+        for handle in handles.iter().step_by(3) {
+            assert!(queue.remove(*handle).is_some());
+        }
+
+        let mut last = -1;
+        while let Some((value, priority)) = queue.pop() {
+            assert!(value % 3 != 0);
+            assert!(priority > last);
+            last = priority;
+        }
+    }
+
+    #[test]
+    fn slots_are_reused() {
+        let mut timers = IndexedPriorityQueue::new();
+        for round in 0..10_000 {
+            let timeout = timers.push(round, round + 100);
+            timers.push(round, round);
+            // One timer fires, the other is cancelled
+            assert_eq!(timers.pop(), Some((round, round)));
+            assert_eq!(timers.remove(timeout), Some((round, round + 100)));
+        }
+
+        // Never more than two timers were pending at once
+        assert!(timers.is_empty());
+        assert_eq!(timers.entries.len(), 2);
+        assert_eq!(timers.positions.len(), 2);
+    }
+
+    #[test]
+    fn stale_handle_to_reused_slot() {
+        let mut queue = IndexedPriorityQueue::new();
+        let old = queue.push("old", 1);
+        queue.pop();
+        let new = queue.push("new", 2);
+
+        // Same slot, but the old handle must not reach the new entry
+        assert_eq!(old.id, new.id);
+        assert!(!queue.contains(old));
+        assert_eq!(queue.get(old), None);
+        assert_eq!(queue.priority(old), None);
+        assert_eq!(queue.decrease_key(old, 0), Err(PriorityError::NotInQueue));
+        assert_eq!(queue.remove(old), None);
+        assert_eq!(queue.get(new), Some(&"new"));
+        assert_eq!(queue.pop(), Some(("new", 2)));
+    }
+
+    #[test]
+    fn change_priority_timers() {
+        let mut timers = IndexedPriorityQueue::new();
+        let heartbeat = timers.push("heartbeat", 30);
+        timers.push("flush", 20);
+
+        // Rescheduling may move the timer either way
+        timers.change_priority(heartbeat, 10).unwrap();
+        assert_eq!(timers.peek(), Some((&"heartbeat", &10)));
+        timers.change_priority(heartbeat, 40).unwrap();
+        assert_eq!(timers.peek(), Some((&"flush", &20)));
+        assert_eq!(timers.priority(heartbeat), Some(&40));
+    }
+}
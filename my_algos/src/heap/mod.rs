@@ -0,0 +1,3 @@
+pub mod d_ary_heap;
+pub mod heap_sort;
+pub mod indexed_priority_queue;
@@ -4,3 +4,5 @@ pub mod sorting;
 pub mod union_find;
 pub mod trie;
 pub mod lru_cache;
+pub mod heap;
+pub mod graph;
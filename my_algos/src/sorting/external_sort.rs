@@ -0,0 +1,405 @@
+//! External merge sort for inputs that do not fit in memory.
+//!
+//! 1. Read records until the memory budget is used up, sort them in memory with `quick_sort`
+//!    and spill them as a sorted run to a temp file.
+//! 2. k-way merge all runs through a min heap that holds one record per run.
+//!    With more than `MAX_FAN_IN` runs, batches of runs are first merged into longer runs
+//!    so we never hold too many files open at once.
+//!
+//! If the whole input fits in the budget no temp file is written at all.
+//! How records are read and written is up to a `RecordFormat`, `Lines` handles
+//! newline-delimited text.
+
+use super::quick_sort::quick_sort_in_place;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 64 MiB
+const DEFAULT_MEMORY_BUDGET: usize = 64 * 1024 * 1024;
+
+// Merging more runs than this at once risks running out of file descriptors
+const MAX_FAN_IN: usize = 128;
+
+/// Tells the sorter how to parse records and how to write them back out.
+/// Runs are written with `write_record` and read back with `read_record`,
+/// so the two have to round trip.
+pub trait RecordFormat {
+    type Record: Ord;
+
+    /// Returns `Ok(None)` once the input is exhausted
+    fn read_record(&mut self, reader: &mut dyn BufRead) -> io::Result<Option<Self::Record>>;
+
+    fn write_record(&mut self, record: &Self::Record, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// Heap bytes owned by the record, counted against the memory budget
+    /// on top of `size_of::<Record>()`
+    fn heap_size(&self, record: &Self::Record) -> usize;
+}
+
+/// Newline-delimited text, every line is one record without its line ending
+#[derive(Default)]
+pub struct Lines;
+
+impl RecordFormat for Lines {
+    type Record = String;
+
+    fn read_record(&mut self, reader: &mut dyn BufRead) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    fn write_record(&mut self, record: &String, writer: &mut dyn Write) -> io::Result<()> {
+        writer.write_all(record.as_bytes())?;
+        writer.write_all(b"\n")
+    }
+
+    fn heap_size(&self, record: &String) -> usize {
+        record.capacity()
+    }
+}
+
+pub struct ExternalSorter<F: RecordFormat = Lines> {
+    memory_budget: usize,
+    temp_dir: PathBuf,
+    format: F,
+}
+
+impl ExternalSorter<Lines> {
+    /// Sorts lines with a 64 MiB budget, spilling to the system temp directory
+    pub fn new() -> Self {
+        Self::with_format(Lines)
+    }
+}
+
+impl Default for ExternalSorter<Lines> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: RecordFormat> ExternalSorter<F> {
+    pub fn with_format(format: F) -> Self {
+        Self {
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            temp_dir: std::env::temp_dir(),
+            format,
+        }
+    }
+
+    /// Approximate number of bytes of records held in memory at once
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Directory the sorted runs are spilled to, it has to exist
+    pub fn temp_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.temp_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sorts every record of `input` into `output`
+    pub fn sort<R: Read, W: Write>(&mut self, input: R, output: W) -> io::Result<()> {
+        let mut reader = BufReader::new(input);
+        let mut writer = BufWriter::new(output);
+        let mut runs = RunFiles::new(&self.temp_dir);
+
+        loop {
+            let (mut chunk, exhausted) = self.read_chunk(&mut reader)?;
+            quick_sort_in_place(&mut chunk);
+
+            // Everything fit in memory, skip the temp files
+            if exhausted && runs.paths.is_empty() {
+                for record in &chunk {
+                    self.format.write_record(record, &mut writer)?;
+                }
+                return writer.flush();
+            }
+
+            if !chunk.is_empty() {
+                self.spill(&chunk, runs.create()?)?;
+            }
+            if exhausted {
+                break;
+            }
+        }
+
+        while runs.paths.len() > MAX_FAN_IN {
+            let mut run_writer = BufWriter::new(runs.create()?);
+            self.merge(&runs.paths[..MAX_FAN_IN], &mut run_writer)?;
+            run_writer.flush()?;
+            runs.remove_first(MAX_FAN_IN)?;
+        }
+
+        self.merge(&runs.paths, &mut writer)?;
+        writer.flush()
+    }
+
+    /// Same as `sort`, but between two files
+    pub fn sort_file<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        input: P,
+        output: Q,
+    ) -> io::Result<()> {
+        self.sort(File::open(input)?, File::create(output)?)
+    }
+
+    /// Reads records until the budget is used up.
+    /// Also returns whether the input is exhausted.
+    fn read_chunk(&mut self, reader: &mut dyn BufRead) -> io::Result<(Vec<F::Record>, bool)> {
+        let mut chunk = Vec::new();
+        let mut used = 0;
+
+        // Always take at least one record, otherwise a tiny budget never makes progress
+        while chunk.is_empty() || used < self.memory_budget {
+            match self.format.read_record(reader)? {
+                Some(record) => {
+                    used += std::mem::size_of::<F::Record>() + self.format.heap_size(&record);
+                    chunk.push(record);
+                }
+                None => return Ok((chunk, true)),
+            }
+        }
+
+        Ok((chunk, false))
+    }
+
+    fn spill(&mut self, chunk: &[F::Record], file: File) -> io::Result<()> {
+        let mut writer = BufWriter::new(file);
+        for record in chunk {
+            self.format.write_record(record, &mut writer)?;
+        }
+        writer.flush()
+    }
+
+    /// k-way merge, the heap holds the smallest unread record of every run.
+    /// Ties go to the earlier run.
+    fn merge(&mut self, runs: &[PathBuf], writer: &mut dyn Write) -> io::Result<()> {
+        let mut readers = runs
+            .iter()
+            .map(|path| File::open(path).map(BufReader::new))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+
+        for (run, reader) in readers.iter_mut().enumerate() {
+            if let Some(record) = self.format.read_record(reader)? {
+                heap.push(Reverse((record, run)));
+            }
+        }
+
+        while let Some(Reverse((record, run))) = heap.pop() {
+            self.format.write_record(&record, writer)?;
+            if let Some(next) = self.format.read_record(&mut readers[run])? {
+                heap.push(Reverse((next, run)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Keeps temp file names unique between sorters running at the same time
+static NEXT_SORT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Temp files of the sorted runs, deleted on drop even if the sort fails half way
+struct RunFiles {
+    dir: PathBuf,
+    sort_id: usize,
+    nf_created: usize,
+    paths: Vec<PathBuf>,
+}
+
+impl RunFiles {
+    fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            sort_id: NEXT_SORT_ID.fetch_add(1, Ordering::Relaxed),
+            nf_created: 0,
+            paths: Vec::new(),
+        }
+    }
+
+    fn create(&mut self) -> io::Result<File> {
+        let name = format!(
+            "my_algos_external_sort_{}_{}_{}.run",
+            std::process::id(),
+            self.sort_id,
+            self.nf_created
+        );
+        let path = self.dir.join(name);
+        let file = File::create(&path)?;
+        self.nf_created += 1;
+        self.paths.push(path);
+        Ok(file)
+    }
+
+    /// Deletes the oldest `count` runs once they were merged into a new one
+    fn remove_first(&mut self, count: usize) -> io::Result<()> {
+        for path in self.paths.drain(..count) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RunFiles {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("my_algos_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn sorts_in_memory() {
+        let mut output = Vec::new();
+        ExternalSorter::new()
+            .sort("pear\napple\nfig\r\nbanana".as_bytes(), &mut output)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "apple\nbanana\nfig\npear\n"
+        );
+    }
+
+    #[test]
+    fn spills_runs_and_cleans_up() {
+        let dir = test_dir("external_sort_spill");
+        let mut rng = thread_rng();
+        let mut lines: Vec<String> = (0..5000).map(|_| rng.gen::<u32>().to_string()).collect();
+        let input = lines.join("\n");
+
+        // A couple of KiB per run forces dozens of runs
+        let mut output = Vec::new();
+        ExternalSorter::new()
+            .memory_budget(4096)
+            .temp_dir(&dir)
+            .sort(input.as_bytes(), &mut output)
+            .unwrap();
+
+        lines.sort();
+        let sorted: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+        assert_eq!(sorted, lines);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn merges_in_several_passes() {
+        let dir = test_dir("external_sort_passes");
+        let mut lines: Vec<String> = (0..1000)
+            .rev()
+            .map(|value| format!("{:04}", value))
+            .collect();
+        let input = lines.join("\n");
+
+        // One record per run, more runs than we merge at once
+        let mut output = Vec::new();
+        ExternalSorter::new()
+            .memory_budget(1)
+            .temp_dir(&dir)
+            .sort(input.as_bytes(), &mut output)
+            .unwrap();
+
+        lines.sort();
+        let sorted: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+        assert_eq!(sorted, lines);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn empty_input() {
+        let mut output = Vec::new();
+        ExternalSorter::new()
+            .sort(io::empty(), &mut output)
+            .unwrap();
+        assert!(output.is_empty());
+    }
+
+    /// Records are `<score>,<name>` lines ordered by score, written back unchanged
+    struct Scored;
+
+    impl RecordFormat for Scored {
+        type Record = (u64, String);
+
+        fn read_record(&mut self, reader: &mut dyn BufRead) -> io::Result<Option<(u64, String)>> {
+            let Some(line) = Lines.read_record(reader)? else {
+                return Ok(None);
+            };
+            let (score, name) = line
+                .split_once(',')
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing comma"))?;
+            let score = score
+                .parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+            Ok(Some((score, name.to_string())))
+        }
+
+        fn write_record(
+            &mut self,
+            record: &(u64, String),
+            writer: &mut dyn Write,
+        ) -> io::Result<()> {
+            writeln!(writer, "{},{}", record.0, record.1)
+        }
+
+        fn heap_size(&self, record: &(u64, String)) -> usize {
+            record.1.capacity()
+        }
+    }
+
+    #[test]
+    fn custom_record_format() {
+        let dir = test_dir("external_sort_custom");
+        let input = "10,ten\n9,nine\n100,hundred\n2,two\n";
+
+        let mut output = Vec::new();
+        ExternalSorter::with_format(Scored)
+            .memory_budget(1)
+            .temp_dir(&dir)
+            .sort(input.as_bytes(), &mut output)
+            .unwrap();
+
+        // Numeric order, not the lexicographic order plain lines would give
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "2,two\n9,nine\n10,ten\n100,hundred\n"
+        );
+
+        let mut output = Vec::new();
+        let result = ExternalSorter::with_format(Scored).sort("oops\n".as_bytes(), &mut output);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir(&dir).unwrap();
+    }
+}
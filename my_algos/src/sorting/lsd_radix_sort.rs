@@ -0,0 +1,197 @@
+//! Least significant digit radix sort for fixed-width integer keys.
+//!
+//! Keys are split into bytes and sorted with one stable counting pass per byte,
+//! starting from the least significant one, so the whole sort is O(BYTES * (n + 256)).
+//! Passes where every key has the same byte are skipped, which makes small values in a wide
+//! type cheap. The counting passes only shuffle `(key, index)` pairs, the elements themselves
+//! are permuted once at the end with swaps, so no `Copy` or `Clone` bound is needed.
+
+/// Anything that can be sorted by `lsd_radix_sort`.
+///
+/// `radix_key` maps the value to an unsigned integer whose order matches the order of the value,
+/// only its lowest `BYTES` bytes are looked at.
+/// Implement it for your own types to radix sort them by an integer field:
+///
+/// ```
+/// use my_algos::sorting::lsd_radix_sort::{lsd_radix_sort, RadixKey};
+///
+/// struct Event {
+///     id: u32,
+/// }
+///
+/// impl RadixKey for Event {
+///     const BYTES: usize = u32::BYTES;
+///
+///     fn radix_key(&self) -> u64 {
+///         self.id.radix_key()
+///     }
+/// }
+///
+/// let mut events = vec![Event { id: 7 }, Event { id: 3 }];
+/// lsd_radix_sort(&mut events);
+/// assert_eq!(events[0].id, 3);
+/// ```
+pub trait RadixKey {
+    const BYTES: usize;
+
+    fn radix_key(&self) -> u64;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl RadixKey for $ty {
+                const BYTES: usize = std::mem::size_of::<$ty>();
+
+                fn radix_key(&self) -> u64 {
+                    *self as u64
+                }
+            }
+        )*
+    };
+}
+
+// Flipping the sign bit moves negative numbers below the positive ones
+macro_rules! impl_radix_key_signed {
+    ($($ty:ty => $unsigned:ty),*) => {
+        $(
+            impl RadixKey for $ty {
+                const BYTES: usize = std::mem::size_of::<$ty>();
+
+                fn radix_key(&self) -> u64 {
+                    ((*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1))) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+// One digit is one byte
+const RADIX: usize = 256;
+
+pub fn lsd_radix_sort<T: RadixKey>(input: &mut [T]) {
+    let keys = input.iter().map(RadixKey::radix_key).collect();
+    sort_by_keys(input, keys, T::BYTES);
+}
+
+/// Stable radix sort by the integer key extracted from each element.
+/// The key is extracted exactly once per element.
+pub fn lsd_radix_sort_by_key<T, K, F>(input: &mut [T], mut key: F)
+where
+    K: RadixKey,
+    F: FnMut(&T) -> K,
+{
+    let keys = input
+        .iter()
+        .map(|element| key(element).radix_key())
+        .collect();
+    sort_by_keys(input, keys, K::BYTES);
+}
+
+fn sort_by_keys<T>(input: &mut [T], keys: Vec<u64>, nf_bytes: usize) {
+    let mut entries: Vec<(u64, usize)> = keys.into_iter().zip(0..).collect();
+    let mut scratch = entries.clone();
+
+    for byte in 0..nf_bytes {
+        let shift = 8 * byte;
+        let digit = |key: u64| ((key >> shift) & 0xff) as usize;
+
+        let mut counts = [0usize; RADIX];
+        for &(key, _) in &entries {
+            counts[digit(key)] += 1;
+        }
+
+        // Every key has the same byte here, the pass would not move anything
+        if counts.contains(&entries.len()) {
+            continue;
+        }
+
+        let mut offsets = [0usize; RADIX];
+        for bucket in 1..RADIX {
+            offsets[bucket] = offsets[bucket - 1] + counts[bucket - 1];
+        }
+
+        for &entry in &entries {
+            let bucket = digit(entry.0);
+            scratch[offsets[bucket]] = entry;
+            offsets[bucket] += 1;
+        }
+
+        std::mem::swap(&mut entries, &mut scratch);
+    }
+
+    let order: Vec<usize> = entries.into_iter().map(|(_, index)| index).collect();
+    apply_permutation(input, &order);
+}
+
+/// Moves `input[order[k]]` to position `k` by walking each cycle of the permutation
+fn apply_permutation<T>(input: &mut [T], order: &[usize]) {
+    let mut placed = vec![false; order.len()];
+
+    for start in 0..order.len() {
+        if placed[start] {
+            continue;
+        }
+
+        let mut current = start;
+        loop {
+            placed[current] = true;
+            let next = order[current];
+            if next == start {
+                break;
+            }
+            input.swap(current, next);
+            current = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn lsd_radix_sort_u32() {
+        let mut rng = thread_rng();
+        let mut input: Vec<u32> = (0..100_000).map(|_| rng.gen()).collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        lsd_radix_sort(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn lsd_radix_sort_signed() {
+        let mut input = vec![i64::MAX, -1, 0, i64::MIN, 42, -42, 1];
+        lsd_radix_sort(&mut input);
+        assert_eq!(input, vec![i64::MIN, -42, -1, 0, 1, 42, i64::MAX]);
+
+        let mut small: Vec<i8> = (-128..=127).rev().collect();
+        lsd_radix_sort(&mut small);
+        assert_eq!(small, (-128..=127).collect::<Vec<i8>>());
+    }
+
+    #[test]
+    fn lsd_radix_sort_by_key_is_stable() {
+        let mut input: Vec<(u16, String)> = (0..5000)
+            .map(|index| ((index * 7919 % 64) as u16, index.to_string()))
+            .collect();
+        let mut expected = input.clone();
+        expected.sort_by_key(|record| record.0);
+
+        lsd_radix_sort_by_key(&mut input, |record| record.0);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn apply_permutation_cycles() {
+        let mut input = vec!['a', 'b', 'c', 'd', 'e'];
+        apply_permutation(&mut input, &[3, 0, 4, 1, 2]);
+        assert_eq!(input, vec!['d', 'a', 'e', 'b', 'c']);
+    }
+}
@@ -0,0 +1,392 @@
+//! Stable, adaptive merge sort in the spirit of Timsort.
+//!
+//! - The input is scanned for natural runs. Strictly descending runs are reversed,
+//!   short runs are extended to `min_run` elements with binary insertion sort.
+//! - Runs are pushed on a stack and merged while the stack violates the Timsort invariants,
+//!   so merges stay balanced and the whole sort is O(n log n), O(n) on presorted input.
+//! - Merging gallops (exponential search) once one side keeps winning,
+//!   which makes merging a run into a much shorter one close to O(log n).
+//!
+//! Equal elements never overtake each other, so sorting by a secondary key and then by the
+//! primary key gives the combined order. The merge buffer clones the left run,
+//! hence the `Clone` bound where `quick_sort` gets away with swaps.
+
+use std::cmp::Ordering;
+
+// Inputs shorter than this are binary insertion sorted in one go
+const MIN_MERGE: usize = 32;
+
+// How many wins in a row before a merge switches to galloping
+const MIN_GALLOP: usize = 7;
+
+pub fn merge_sort<T>(mut input: Vec<T>) -> Vec<T>
+where
+    T: Eq + std::cmp::PartialOrd + Copy,
+{
+    merge_sort_by_less(&mut input, &mut |a: &T, b: &T| a < b);
+    input
+}
+
+pub fn merge_sort_by<T, F>(input: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_sort_by_less(input, &mut |a: &T, b: &T| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts by the key extracted from each element.
+/// The key is recomputed on every comparison, so keep `key` cheap.
+pub fn merge_sort_by_key<T, K, F>(input: &mut [T], mut key: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_by_less(input, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+/// A sorted stretch of the input, `input[start..start + len]`
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+fn merge_sort_by_less<T, F>(input: &mut [T], is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let input_len = input.len();
+    if input_len < 2 {
+        return;
+    }
+
+    if input_len < MIN_MERGE {
+        let run_len = count_run_and_make_ascending(input, is_less);
+        binary_insertion_sort(input, run_len, is_less);
+        return;
+    }
+
+    let min_run = min_run_length(input_len);
+    let mut runs: Vec<Run> = Vec::new();
+    let mut start = 0;
+
+    while start < input_len {
+        let mut run_len = count_run_and_make_ascending(&mut input[start..], is_less);
+
+        if run_len < min_run {
+            let forced_len = min_run.min(input_len - start);
+            binary_insertion_sort(&mut input[start..start + forced_len], run_len, is_less);
+            run_len = forced_len;
+        }
+
+        runs.push(Run {
+            start,
+            len: run_len,
+        });
+        merge_collapse(input, &mut runs, is_less);
+        start += run_len;
+    }
+
+    // Merge whatever is left on the stack, smallest runs first
+    while runs.len() > 1 {
+        let at = runs.len() - 2;
+        merge_at(input, &mut runs, at, is_less);
+    }
+}
+
+/// Picks a run length in [MIN_MERGE / 2, MIN_MERGE] such that
+/// `input_len / min_run` is a power of two or slightly less, which keeps merges balanced
+fn min_run_length(mut input_len: usize) -> usize {
+    let mut remainder = 0;
+    while input_len >= MIN_MERGE {
+        remainder |= input_len & 1;
+        input_len >>= 1;
+    }
+
+    input_len + remainder
+}
+
+/// Returns the length of the run at the start of the slice.
+/// Descending runs must be strictly descending, otherwise reversing them breaks stability.
+fn count_run_and_make_ascending<T, F>(input: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let input_len = input.len();
+    if input_len < 2 {
+        return input_len;
+    }
+
+    let mut run_end = 2;
+    if is_less(&input[1], &input[0]) {
+        while run_end < input_len && is_less(&input[run_end], &input[run_end - 1]) {
+            run_end += 1;
+        }
+        input[..run_end].reverse();
+    } else {
+        while run_end < input_len && !is_less(&input[run_end], &input[run_end - 1]) {
+            run_end += 1;
+        }
+    }
+
+    run_end
+}
+
+/// `input[..sorted_len]` is already sorted, insert the rest one by one.
+/// New elements go after any equal ones to stay stable.
+fn binary_insertion_sort<T, F>(input: &mut [T], sorted_len: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for index in sorted_len.max(1)..input.len() {
+        let position = input[..index].partition_point(|element| !is_less(&input[index], element));
+        input[position..=index].rotate_right(1);
+    }
+}
+
+/// Restores the invariants on the top of the run stack
+/// runs[n - 1].len > runs[n].len + runs[n + 1].len and runs[n].len > runs[n + 1].len,
+/// also checking one run further down as in the fixed Java/Python versions.
+fn merge_collapse<T, F>(input: &mut [T], runs: &mut Vec<Run>, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    while runs.len() > 1 {
+        let n = runs.len() - 2;
+
+        if (n > 0 && runs[n - 1].len <= runs[n].len + runs[n + 1].len)
+            || (n > 1 && runs[n - 2].len <= runs[n - 1].len + runs[n].len)
+        {
+            let at = if runs[n - 1].len < runs[n + 1].len {
+                n - 1
+            } else {
+                n
+            };
+            merge_at(input, runs, at, is_less);
+        } else if runs[n].len <= runs[n + 1].len {
+            merge_at(input, runs, n, is_less);
+        } else {
+            break;
+        }
+    }
+}
+
+/// Merges runs[at] with runs[at + 1]
+fn merge_at<T, F>(input: &mut [T], runs: &mut Vec<Run>, at: usize, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let start = runs[at].start;
+    let mid = runs[at].len;
+    let end = runs[at + 1].start + runs[at + 1].len;
+
+    runs[at].len += runs[at + 1].len;
+    runs.remove(at + 1);
+
+    merge(&mut input[start..end], mid, is_less);
+}
+
+/// Merges the sorted halves `input[..mid]` and `input[mid..]`
+fn merge<T, F>(input: &mut [T], mid: usize, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    // Left elements not greater than the first right element are already in place
+    let skip = gallop(&input[..mid], |element| !is_less(&input[mid], element));
+    let input = &mut input[skip..];
+    let mid = mid - skip;
+    if mid == 0 {
+        return;
+    }
+
+    // Right elements not smaller than the last left element are already in place
+    let right_len = gallop(&input[mid..], |element| is_less(element, &input[mid - 1]));
+    merge_lo(&mut input[..mid + right_len], mid, is_less);
+}
+
+/// Copies the left run into a buffer and merges forwards.
+/// Slots in `input[dest..j]` hold stale values that are overwritten by swaps,
+/// only `buffer[i..]` and `input[j..]` are still to be merged.
+fn merge_lo<T, F>(input: &mut [T], mid: usize, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let input_len = input.len();
+    let mut buffer = input[..mid].to_vec();
+    let (mut i, mut j, mut dest) = (0, mid, 0);
+    let mut min_gallop = MIN_GALLOP;
+
+    'merge: while i < mid && j < input_len {
+        let mut left_wins = 0;
+        let mut right_wins = 0;
+
+        // One element at a time until one side starts winning consistently
+        loop {
+            // Ties go to the left run, that is what keeps the sort stable
+            if is_less(&input[j], &buffer[i]) {
+                input.swap(dest, j);
+                j += 1;
+                right_wins += 1;
+                left_wins = 0;
+            } else {
+                std::mem::swap(&mut input[dest], &mut buffer[i]);
+                i += 1;
+                left_wins += 1;
+                right_wins = 0;
+            }
+            dest += 1;
+
+            if i == mid || j == input_len {
+                break 'merge;
+            }
+            if left_wins >= min_gallop || right_wins >= min_gallop {
+                break;
+            }
+        }
+
+        // Galloping, move whole blocks found by exponential search
+        loop {
+            let left_count = gallop(&buffer[i..], |element| !is_less(&input[j], element));
+            for _ in 0..left_count {
+                std::mem::swap(&mut input[dest], &mut buffer[i]);
+                i += 1;
+                dest += 1;
+            }
+            if i == mid {
+                break 'merge;
+            }
+
+            let right_count = gallop(&input[j..], |element| is_less(element, &buffer[i]));
+            for _ in 0..right_count {
+                input.swap(dest, j);
+                j += 1;
+                dest += 1;
+            }
+            if j == input_len {
+                break 'merge;
+            }
+
+            min_gallop = min_gallop.saturating_sub(1).max(1);
+            if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                // Galloping did not pay off, make it harder to get back in
+                min_gallop += 2;
+                break;
+            }
+        }
+    }
+
+    // Right leftovers are already in place since dest == j once the buffer is empty
+    while i < mid {
+        std::mem::swap(&mut input[dest], &mut buffer[i]);
+        i += 1;
+        dest += 1;
+    }
+}
+
+/// Length of the prefix of `sorted` for which `predicate` holds.
+/// Probes 1, 2, 4, ... elements in and then binary searches the last gap,
+/// so finding a short prefix is cheap no matter how long the slice is.
+fn gallop<T, P>(sorted: &[T], mut predicate: P) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let mut bound = 1;
+    while bound <= sorted.len() && predicate(&sorted[bound - 1]) {
+        bound *= 2;
+    }
+
+    let low = bound / 2;
+    let high = (bound - 1).min(sorted.len());
+    low + sorted[low..high].partition_point(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{thread_rng, Rng};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Tagged {
+        key: u8,
+        tag: usize,
+    }
+
+    fn tagged_records(len: usize, nf_keys: u8) -> Vec<Tagged> {
+        let mut rng = thread_rng();
+        (0..len)
+            .map(|tag| Tagged {
+                key: rng.gen_range(0..nf_keys),
+                tag,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merge_sort_u32() {
+        assert_eq!(merge_sort(vec![100, 21, 213, 4]), vec![4, 21, 100, 213]);
+        assert_eq!(merge_sort(Vec::<u32>::new()), vec![]);
+    }
+
+    #[test]
+    fn merge_sort_large() {
+        let result: Vec<i32> = (0..100_000).collect();
+        let mut input = result.clone();
+        input.shuffle(&mut thread_rng());
+
+        assert_eq!(merge_sort(input), result);
+        assert_eq!(merge_sort(result.iter().rev().copied().collect()), result);
+    }
+
+    #[test]
+    fn merge_sort_natural_runs() {
+        // Long ascending and descending stretches exercise run detection and galloping
+        let input: Vec<i32> = (0..5000)
+            .chain((0..5000).rev())
+            .chain(10_000..20_000)
+            .chain((-3000..3000).step_by(3))
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        assert_eq!(merge_sort(input), expected);
+    }
+
+    #[test]
+    fn merge_sort_is_stable() {
+        let mut input = tagged_records(50_000, 16);
+        let mut expected = input.clone();
+        expected.sort_by_key(|record| record.key);
+
+        merge_sort_by_key(&mut input, |record| record.key);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn merge_sort_secondary_then_primary_key() {
+        let mut input: Vec<(u8, String)> = (0..2000)
+            .map(|index| ((index % 7) as u8, format!("{:04}", (index * 37) % 2000)))
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        merge_sort_by(&mut input, |a, b| a.1.cmp(&b.1));
+        merge_sort_by(&mut input, |a, b| a.0.cmp(&b.0));
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn gallop_finds_prefix() {
+        let sorted: Vec<i32> = (0..100).collect();
+
+        assert_eq!(gallop(&sorted, |&value| value < 0), 0);
+        assert_eq!(gallop(&sorted, |&value| value < 37), 37);
+        assert_eq!(gallop(&sorted, |&value| value < 1000), 100);
+    }
+}
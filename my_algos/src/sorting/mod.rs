@@ -1 +1,8 @@
+pub mod external_sort;
+pub mod lsd_radix_sort;
+pub mod merge_sort;
+pub mod msd_radix_sort;
+pub mod par_quick_sort;
 pub mod quick_sort;
+pub mod select;
+pub mod sorter;
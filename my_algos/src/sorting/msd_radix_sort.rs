@@ -0,0 +1,159 @@
+//! Most significant digit radix sort for byte-string keys (`&str`, `String`, `&[u8]`, `Vec<u8>`).
+//!
+//! Each level buckets the slice by the byte at the current depth and recurses into every bucket
+//! with the next byte. Keys that end at this depth go into bucket 0 ahead of all others,
+//! which gives the usual lexicographic order where a prefix sorts first.
+//! Buckets are filled in place with swaps (American flag sort), so no buffer is needed,
+//! and small buckets are finished with insertion sort on the remaining suffixes.
+//! Only the smaller buckets are sorted recursively, the largest one is taken on by the same
+//! call in a loop. Each of those is at most half the slice, so the call stack stays
+//! O(log n) deep even when the keys share a very long prefix.
+//! Like `quick_sort` it is not stable.
+
+// One bucket per byte value plus one for keys that already ended
+const NF_BUCKETS: usize = 257;
+
+// Below this bucket size insertion sort beats another counting pass
+const INSERTION_SORT_THRESHOLD: usize = 32;
+
+pub fn msd_radix_sort<T: AsRef<[u8]>>(input: &mut [T]) {
+    sort_from_depth(input, 0);
+}
+
+fn bucket<T: AsRef<[u8]>>(key: &T, depth: usize) -> usize {
+    key.as_ref().get(depth).map_or(0, |&byte| byte as usize + 1)
+}
+
+fn sort_from_depth<T: AsRef<[u8]>>(mut input: &mut [T], mut depth: usize) {
+    // Allocated once and reused by every round of the loop
+    let mut counts = vec![0usize; NF_BUCKETS];
+    let mut ends = vec![0usize; NF_BUCKETS];
+    let mut starts = vec![0usize; NF_BUCKETS];
+    let mut next = vec![0usize; NF_BUCKETS];
+
+    loop {
+        if input.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort(input, depth);
+            return;
+        }
+
+        counts.fill(0);
+        for key in input.iter() {
+            counts[bucket(key, depth)] += 1;
+        }
+
+        let mut total = 0;
+        for current in 0..NF_BUCKETS {
+            starts[current] = total;
+            total += counts[current];
+            ends[current] = total;
+        }
+
+        // next[b] is the first slot of bucket b that does not yet hold one of its keys
+        next.copy_from_slice(&starts);
+        for current in 0..NF_BUCKETS {
+            while next[current] < ends[current] {
+                let target = bucket(&input[next[current]], depth);
+                if target == current {
+                    next[current] += 1;
+                } else {
+                    input.swap(next[current], next[target]);
+                    next[target] += 1;
+                }
+            }
+        }
+
+        // Bucket 0 holds keys that ended, they are all equal
+        let largest = (1..NF_BUCKETS)
+            .max_by_key(|&current| counts[current])
+            .unwrap();
+        for current in 1..NF_BUCKETS {
+            if current != largest && counts[current] > 1 {
+                sort_from_depth(&mut input[starts[current]..ends[current]], depth + 1);
+            }
+        }
+
+        input = &mut input[starts[largest]..ends[largest]];
+        depth += 1;
+    }
+}
+
+/// All keys share their first `depth` bytes, so only the suffixes are compared
+fn insertion_sort<T: AsRef<[u8]>>(input: &mut [T], depth: usize) {
+    for index in 1..input.len() {
+        let mut curr = index;
+        while curr > 0 && suffix(&input[curr], depth) < suffix(&input[curr - 1], depth) {
+            input.swap(curr, curr - 1);
+            curr -= 1;
+        }
+    }
+}
+
+fn suffix<T: AsRef<[u8]>>(key: &T, depth: usize) -> &[u8] {
+    key.as_ref().get(depth..).unwrap_or(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn msd_radix_sort_str() {
+        let mut input = vec!["she", "sells", "sea", "shells", "by", "the", "sea", "", "s"];
+        msd_radix_sort(&mut input);
+        assert_eq!(
+            input,
+            vec!["", "by", "s", "sea", "sea", "sells", "she", "shells", "the"]
+        );
+    }
+
+    #[test]
+    fn msd_radix_sort_large_ascii_keys() {
+        let mut rng = thread_rng();
+        let mut input: Vec<String> = (0..20_000)
+            .map(|_| {
+                let len = rng.gen_range(0..12);
+                (0..len)
+                    .map(|_| rng.gen_range(b'a'..=b'f') as char)
+                    .collect()
+            })
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        msd_radix_sort(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn msd_radix_sort_bytes_and_unicode() {
+        let mut bytes: Vec<Vec<u8>> = vec![vec![255, 0], vec![0], vec![], vec![255], vec![0, 0]];
+        msd_radix_sort(&mut bytes);
+        assert_eq!(
+            bytes,
+            vec![vec![], vec![0], vec![0, 0], vec![255], vec![255, 0]]
+        );
+
+        // Byte order of UTF-8 matches char order
+        let mut words = vec!["zebra", "équipe", "apple", "Zoo", "ångström"];
+        let mut expected = words.clone();
+        expected.sort();
+        msd_radix_sort(&mut words);
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn msd_radix_sort_long_shared_prefix() {
+        // One level per shared byte used to mean one stack frame per byte
+        let prefix = "x".repeat(100_000);
+        let mut input: Vec<String> = (0..300)
+            .map(|value| format!("{}{}", prefix, value * 7919 % 300))
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        msd_radix_sort(&mut input);
+        assert_eq!(input, expected);
+    }
+}
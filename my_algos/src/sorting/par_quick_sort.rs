@@ -0,0 +1,144 @@
+//! Fork-join version of `quick_sort`.
+//!
+//! After each partition the two halves are independent, so we sort them at the same time
+//! with `std::thread::scope`. Scoped threads can borrow the halves of the slice directly,
+//! which the `threadpool` crate cannot do since its jobs have to be `'static`.
+//! We only fork until every core has work, below that each thread runs the sequential
+//! introsort from `quick_sort`, which also keeps the worst case at O(n log n).
+
+use super::quick_sort::{choose_pivot, partition3_by, sort_by_less};
+use std::cmp::Ordering;
+use std::thread;
+
+// Below this length spawning a thread costs more than it saves
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// Parallel drop-in for `quick_sort`, produces exactly the same output
+pub fn par_quick_sort<T>(mut input: Vec<T>) -> Vec<T>
+where
+    T: Eq + std::cmp::PartialOrd + Copy + Send,
+{
+    par_sort_by_less(&mut input, &|a: &T, b: &T| a < b);
+    input
+}
+
+/// Parallel counterpart of `quick_sort_by`.
+/// The comparator is shared between threads, hence `Fn + Sync` instead of `FnMut`.
+pub fn par_quick_sort_by<T, F>(input: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_sort_by_less(input, &|a: &T, b: &T| compare(a, b) == Ordering::Less);
+}
+
+fn par_sort_by_less<T, F>(input: &mut [T], is_less: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    let nf_threads = thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1);
+
+    fork_join(input, is_less, nf_threads);
+}
+
+/// `nf_threads` is the number of threads this call may still occupy,
+/// it is split between the two halves on every fork.
+fn fork_join<T, F>(input: &mut [T], is_less: &F, nf_threads: usize)
+where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    if nf_threads <= 1 || input.len() < PARALLEL_THRESHOLD {
+        sort_by_less(input, &mut |a: &T, b: &T| is_less(a, b));
+        return;
+    }
+
+    let slice_len = input.len();
+    let pivot_index = choose_pivot(input, &mut |a: &T, b: &T| is_less(a, b));
+    input.swap(pivot_index, slice_len - 1);
+    let (lt, gt) = partition3_by(input, &mut |a: &T, b: &T| is_less(a, b));
+
+    let (left, right) = input.split_at_mut(lt);
+    let right = &mut right[gt - lt..];
+
+    let left_threads = nf_threads / 2;
+    thread::scope(|scope| {
+        scope.spawn(|| fork_join(left, is_less, left_threads));
+        fork_join(right, is_less, nf_threads - left_threads);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::quick_sort::quick_sort;
+    use super::*;
+    use rand::{thread_rng, Rng};
+    use std::time::Instant;
+
+    fn random_i64s(len: usize) -> Vec<i64> {
+        let mut rng = thread_rng();
+        (0..len).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn par_quick_sort_small() {
+        assert_eq!(par_quick_sort(vec![100, 21, 213, 4]), vec![4, 21, 100, 213]);
+        assert_eq!(par_quick_sort(Vec::<i32>::new()), vec![]);
+    }
+
+    #[test]
+    fn par_quick_sort_matches_sequential() {
+        let input = random_i64s(500_000);
+        assert_eq!(par_quick_sort(input.clone()), quick_sort(input.clone()));
+
+        // Force forking even on single core machines
+        let mut forked = input.clone();
+        fork_join(&mut forked, &|a: &i64, b: &i64| a < b, 8);
+        assert_eq!(forked, quick_sort(input));
+
+        let sorted: Vec<i64> = (0..300_000).collect();
+        assert_eq!(
+            par_quick_sort(sorted.iter().rev().copied().collect()),
+            sorted
+        );
+    }
+
+    #[test]
+    fn par_quick_sort_by_strings() {
+        let mut input: Vec<String> = (0..100_000)
+            .map(|value| (value * 31 % 1000).to_string())
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+
+        par_quick_sort_by(&mut input, |a, b| a.cmp(b));
+        assert_eq!(input, expected);
+    }
+
+    /// Speed comparison, run with
+    /// `cargo test --release par_quick_sort_speedup -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn par_quick_sort_speedup() {
+        let input = random_i64s(10_000_000);
+
+        let start = Instant::now();
+        let sequential = quick_sort(input.clone());
+        let sequential_time = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = par_quick_sort(input);
+        let parallel_time = start.elapsed();
+
+        assert_eq!(sequential, parallel);
+        println!(
+            "10M i64: quick_sort {:?}, par_quick_sort {:?} ({:.2}x)",
+            sequential_time,
+            parallel_time,
+            sequential_time.as_secs_f64() / parallel_time.as_secs_f64()
+        );
+    }
+}
@@ -1,52 +1,370 @@
+use super::sorter::StatsSink;
+use std::cmp::Ordering;
+
 /// This QuickSort implementation is generic as long as the datatype implements certain traits
-/// We assume the pivot is the right most element
+/// `partition` and `partition3` assume the pivot is the right most element, so the caller picks
+/// a good pivot (median-of-three or Tukey's ninther) and swaps it to the end first.
+/// We use the three-way `partition3` so runs of equal keys are settled in a single pass.
+///
+/// To stay O(n log n) on adversarial inputs this is really an introsort:
+/// - slices shorter than `INSERTION_SORT_THRESHOLD` are finished with insertion sort
+/// - once the recursion gets deeper than 2 * log2(n) we fall back to heap sort
 ///
+/// Internally everything is driven by an `is_less` comparator and only ever swaps elements,
+/// so the `_by`, `_by_key` and `Ord` variants below work for `String`s, `f64`s or any other
+/// type that is neither `Copy` nor `Eq`.
 pub fn quick_sort<T>(mut input: Vec<T>) -> Vec<T>
 where
     T: Eq + std::cmp::PartialOrd + Copy,
 {
-    let input_len = input.len();
     quick_sort_recursive(&mut input);
     input
 }
 
-pub fn quick_sort_recursive<T: Eq + std::cmp::PartialOrd + Copy>(input: &mut [T])
+// Below this length insertion sort beats partitioning
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+// Above this length we pay for a ninther instead of a plain median-of-three
+const NINTHER_THRESHOLD: usize = 128;
+
+pub fn quick_sort_recursive<T>(input: &mut [T])
 where
-    T: Eq + std::cmp::PartialOrd + Copy,
+    T: std::cmp::PartialOrd,
 {
-    let slice_len = input.len();
+    sort_by_less(input, &mut |a: &T, b: &T| a < b);
+}
+
+/// In-place sort for any totally ordered type, elements are moved instead of copied
+pub fn quick_sort_in_place<T: Ord>(input: &mut [T]) {
+    sort_by_less(input, &mut |a: &T, b: &T| a < b);
+}
+
+/// Sorts with a comparator, e.g. `quick_sort_by(&mut floats, |a, b| a.total_cmp(b))`
+pub fn quick_sort_by<T, F>(input: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort_by_less(input, &mut |a: &T, b: &T| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts by the key extracted from each element.
+/// The key is recomputed on every comparison, so keep `key` cheap.
+pub fn quick_sort_by_key<T, K, F>(input: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    sort_by_less(input, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+/// Lets any slice (and through deref any `Vec`) call our sorts as methods
+pub trait QuickSortExt<T> {
+    fn quick_sort(&mut self)
+    where
+        T: Ord;
+
+    fn quick_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    fn quick_sort_by_key<K, F>(&mut self, key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K;
+}
 
-    if slice_len > 0 {
-        let partition_index = partition(input);
-        quick_sort_recursive(&mut input[0..partition_index]);
-        quick_sort_recursive(&mut input[partition_index + 1..slice_len]);
+impl<T> QuickSortExt<T> for [T] {
+    fn quick_sort(&mut self)
+    where
+        T: Ord,
+    {
+        quick_sort_in_place(self);
+    }
+
+    fn quick_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        quick_sort_by(self, compare);
+    }
+
+    fn quick_sort_by_key<K, F>(&mut self, key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        quick_sort_by_key(self, key);
     }
 }
 
-pub fn partition<T: Eq + std::cmp::PartialOrd + Copy>(input: &mut [T]) -> usize
+pub(crate) fn sort_by_less<T, F>(input: &mut [T], is_less: &mut F)
 where
-    T: Eq + std::cmp::PartialOrd + Copy,
+    F: FnMut(&T, &T) -> bool,
+{
+    sort_by_less_with_sink(input, is_less, &mut ());
+}
+
+/// Same as `sort_by_less`, but reports swaps and recursion depth to `sink`
+pub(crate) fn sort_by_less_with_sink<T, F, S>(input: &mut [T], is_less: &mut F, sink: &mut S)
+where
+    F: FnMut(&T, &T) -> bool,
+    S: StatsSink,
+{
+    let depth_limit = 2 * floor_log2(input.len());
+    introsort(input, depth_limit, 1, is_less, sink);
+}
+
+/// Recurse into the smaller half and loop on the larger one,
+/// so the stack never grows beyond O(log n) frames.
+fn introsort<T, F, S>(
+    mut input: &mut [T],
+    mut depth_limit: usize,
+    depth: usize,
+    is_less: &mut F,
+    sink: &mut S,
+) where
+    F: FnMut(&T, &T) -> bool,
+    S: StatsSink,
+{
+    sink.on_enter(depth);
+
+    loop {
+        let slice_len = input.len();
+
+        if slice_len <= INSERTION_SORT_THRESHOLD {
+            insertion_sort(input, is_less, sink);
+            return;
+        }
+
+        if depth_limit == 0 {
+            heap_sort(input, is_less, sink);
+            return;
+        }
+        depth_limit -= 1;
+
+        let pivot_index = choose_pivot(input, is_less);
+        input.swap(pivot_index, slice_len - 1);
+        sink.on_swap();
+        let (lt, gt) = partition3_with_sink(input, is_less, sink);
+
+        // Everything in input[lt..gt] equals the pivot and is already in place
+        let (left, right) = input.split_at_mut(lt);
+        let right = &mut right[gt - lt..];
+
+        if left.len() < right.len() {
+            introsort(left, depth_limit, depth + 1, is_less, sink);
+            input = right;
+        } else {
+            introsort(right, depth_limit, depth + 1, is_less, sink);
+            input = left;
+        }
+    }
+}
+
+/// Lomuto partition around the right most element.
+/// Returns the final index of the pivot, everything left of it is smaller.
+pub fn partition<T>(input: &mut [T]) -> usize
+where
+    T: std::cmp::PartialOrd,
 {
-    // We already checked slice is not empty
-    let pivot_value = input.last().copied().unwrap();
+    partition_by(input, &mut |a: &T, b: &T| a < b)
+}
+
+pub(crate) fn partition_by<T, F>(input: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // We already checked slice is not empty.
+    // Park the pivot up front so we can borrow it while swapping the rest.
+    let last = input.len() - 1;
+    input.swap(0, last);
+
+    let (pivot, rest) = input.split_first_mut().unwrap();
     let mut temp_pivot_index = 0;
-    let slice_len = input.len();
 
-    for index in 0..slice_len {
-        if input[index] < pivot_value {
-            input.swap(temp_pivot_index, index);
+    for index in 0..rest.len() {
+        if is_less(&rest[index], pivot) {
+            rest.swap(temp_pivot_index, index);
             temp_pivot_index += 1;
         }
     }
 
     // Swap the pivot into right position
-    if input[temp_pivot_index] > pivot_value {
-        input.swap(temp_pivot_index, slice_len - 1);
-    }
+    input.swap(0, temp_pivot_index);
 
     temp_pivot_index
 }
 
+/// Dutch national flag partition around the right most element.
+/// Returns `(lt, gt)` such that afterwards
+/// `input[..lt] < pivot`, `input[lt..gt] == pivot` and `input[gt..] > pivot`.
+/// The pivot band is never empty, so `lt < gt` for a non-empty slice.
+pub fn partition3<T>(input: &mut [T]) -> (usize, usize)
+where
+    T: std::cmp::PartialOrd,
+{
+    partition3_by(input, &mut |a: &T, b: &T| a < b)
+}
+
+pub(crate) fn partition3_by<T, F>(input: &mut [T], is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    partition3_with_sink(input, is_less, &mut ())
+}
+
+fn partition3_with_sink<T, F, S>(input: &mut [T], is_less: &mut F, sink: &mut S) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+    S: StatsSink,
+{
+    // We already checked slice is not empty.
+    // Park the pivot up front so we can borrow it while swapping the rest.
+    let last = input.len() - 1;
+    input.swap(0, last);
+    sink.on_swap();
+
+    let (pivot, rest) = input.split_first_mut().unwrap();
+    let mut lt = 0;
+    let mut index = 0;
+    let mut gt = rest.len();
+
+    while index < gt {
+        if is_less(&rest[index], pivot) {
+            rest.swap(lt, index);
+            sink.on_swap();
+            lt += 1;
+            index += 1;
+        } else if is_less(pivot, &rest[index]) {
+            gt -= 1;
+            rest.swap(index, gt);
+            sink.on_swap();
+        } else {
+            index += 1;
+        }
+    }
+
+    // rest[..lt] shifted by one is input[1..=lt], moving the pivot to lt keeps that band smaller
+    input.swap(0, lt);
+    sink.on_swap();
+
+    (lt, gt + 1)
+}
+
+/// Returns the index of the pivot to use for this slice.
+/// Median-of-three for medium slices, Tukey's ninther (median of three medians) for large ones.
+/// Expects at least 3 elements.
+pub(crate) fn choose_pivot<T, F>(input: &[T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let slice_len = input.len();
+    let mid = slice_len / 2;
+    let last = slice_len - 1;
+
+    if slice_len < NINTHER_THRESHOLD {
+        return median_of_three(input, 0, mid, last, is_less);
+    }
+
+    let step = slice_len / 8;
+    let low = median_of_three(input, 0, step, 2 * step, is_less);
+    let middle = median_of_three(input, mid - step, mid, mid + step, is_less);
+    let high = median_of_three(input, last - 2 * step, last - step, last, is_less);
+
+    median_of_three(input, low, middle, high, is_less)
+}
+
+fn median_of_three<T, F>(input: &[T], a: usize, b: usize, c: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if is_less(&input[a], &input[b]) {
+        if is_less(&input[b], &input[c]) {
+            b
+        } else if is_less(&input[a], &input[c]) {
+            c
+        } else {
+            a
+        }
+    } else if is_less(&input[a], &input[c]) {
+        a
+    } else if is_less(&input[b], &input[c]) {
+        c
+    } else {
+        b
+    }
+}
+
+fn insertion_sort<T, F, S>(input: &mut [T], is_less: &mut F, sink: &mut S)
+where
+    F: FnMut(&T, &T) -> bool,
+    S: StatsSink,
+{
+    for index in 1..input.len() {
+        let mut curr = index;
+        while curr > 0 && is_less(&input[curr], &input[curr - 1]) {
+            input.swap(curr, curr - 1);
+            sink.on_swap();
+            curr -= 1;
+        }
+    }
+}
+
+/// Fallback used once quick sort recursed too deep, guarantees O(n log n)
+fn heap_sort<T, F, S>(input: &mut [T], is_less: &mut F, sink: &mut S)
+where
+    F: FnMut(&T, &T) -> bool,
+    S: StatsSink,
+{
+    let slice_len = input.len();
+
+    // Build a max heap bottom up
+    for index in (0..slice_len / 2).rev() {
+        sift_down(input, index, is_less, sink);
+    }
+
+    // Move the current max to the end and shrink the heap
+    for end in (1..slice_len).rev() {
+        input.swap(0, end);
+        sink.on_swap();
+        sift_down(&mut input[..end], 0, is_less, sink);
+    }
+}
+
+fn sift_down<T, F, S>(heap: &mut [T], mut node: usize, is_less: &mut F, sink: &mut S)
+where
+    F: FnMut(&T, &T) -> bool,
+    S: StatsSink,
+{
+    loop {
+        let mut largest = node;
+        let left = 2 * node + 1;
+        let right = left + 1;
+
+        if left < heap.len() && is_less(&heap[largest], &heap[left]) {
+            largest = left;
+        }
+        if right < heap.len() && is_less(&heap[largest], &heap[right]) {
+            largest = right;
+        }
+        if largest == node {
+            return;
+        }
+
+        heap.swap(node, largest);
+        sink.on_swap();
+        node = largest;
+    }
+}
+
+fn floor_log2(value: usize) -> usize {
+    if value == 0 {
+        0
+    } else {
+        value.ilog2() as usize
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,4 +395,152 @@ mod tests {
 
         assert_eq!(quick_sort(input), result);
     }
+
+    #[test]
+    fn quick_sort_empty_and_single() {
+        assert_eq!(quick_sort(Vec::<i32>::new()), vec![]);
+        assert_eq!(quick_sort(vec![7]), vec![7]);
+    }
+
+    // The old right-most pivot recursed n deep on these and overflowed the stack
+    #[test]
+    fn quick_sort_large_sorted_and_reversed() {
+        let result: Vec<i64> = (0..200_000).collect();
+
+        assert_eq!(quick_sort(result.clone()), result);
+        assert_eq!(quick_sort(result.iter().rev().copied().collect()), result);
+    }
+
+    #[test]
+    fn quick_sort_organ_pipe_and_sawtooth() {
+        let organ_pipe: Vec<i32> = (0..50_000).chain((0..50_000).rev()).collect();
+        let sawtooth: Vec<i32> = (0..100_000).map(|value| value % 97).collect();
+
+        for input in [organ_pipe, sawtooth] {
+            let mut expected = input.clone();
+            expected.sort();
+            assert_eq!(quick_sort(input), expected);
+        }
+    }
+
+    #[test]
+    fn quick_sort_all_equal() {
+        let input = vec![42u8; 100_000];
+        assert_eq!(quick_sort(input.clone()), input);
+    }
+
+    /// Classic input built to defeat median-of-three pivoting
+    #[test]
+    fn quick_sort_median_of_three_killer() {
+        let half = 50_000;
+        let mut input = vec![0; 2 * half];
+        for index in 0..half {
+            if index % 2 == 0 {
+                input[index] = index + 1;
+            } else {
+                input[index] = half + index + (half % 2);
+            }
+            input[half + index] = 2 * (index + 1);
+        }
+
+        let mut expected = input.clone();
+        expected.sort();
+        assert_eq!(quick_sort(input), expected);
+    }
+
+    #[test]
+    fn quick_sort_duplicate_heavy() {
+        let status_codes = [200, 200, 200, 404, 500, 301, 200, 404];
+        let input: Vec<u16> = (0..100_000)
+            .map(|index| status_codes[index % status_codes.len()])
+            .collect();
+
+        let mut expected = input.clone();
+        expected.sort();
+        assert_eq!(quick_sort(input), expected);
+    }
+
+    #[test]
+    fn partition3_groups_pivot_band() {
+        let mut input = [3, 5, 1, 3, 7, 3, 2, 3];
+        let (lt, gt) = partition3(&mut input);
+
+        assert_eq!((lt, gt), (2, 6));
+        assert!(input[..lt].iter().all(|&value| value < 3));
+        assert!(input[lt..gt].iter().all(|&value| value == 3));
+        assert!(input[gt..].iter().all(|&value| value > 3));
+    }
+
+    #[test]
+    fn heap_sort_fallback() {
+        let result: Vec<i32> = (0..1000).collect();
+        let mut input = result.clone();
+        input.shuffle(&mut thread_rng());
+
+        heap_sort(&mut input, &mut |a: &i32, b: &i32| a < b, &mut ());
+        assert_eq!(input, result);
+    }
+
+    #[test]
+    fn partition_places_pivot() {
+        let mut input = [4, 9, 1, 7, 5];
+        let pivot_index = partition(&mut input);
+
+        assert_eq!(pivot_index, 2);
+        assert_eq!(input[pivot_index], 5);
+        assert!(input[..pivot_index].iter().all(|&value| value < 5));
+        assert!(input[pivot_index + 1..].iter().all(|&value| value > 5));
+    }
+
+    #[test]
+    fn quick_sort_strings_in_place() {
+        let mut input: Vec<String> = ["pear", "apple", "fig", "banana", "apple"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+
+        input.quick_sort();
+        assert_eq!(input, vec!["apple", "apple", "banana", "fig", "pear"]);
+    }
+
+    #[test]
+    fn quick_sort_by_floats() {
+        let mut input: Vec<f64> = (0..1000)
+            .map(|value| ((value * 7919) % 1000) as f64 / 10.0)
+            .collect();
+        let mut expected = input.clone();
+        expected.sort_by(|a, b| a.total_cmp(b));
+
+        quick_sort_by(&mut input, |a, b| a.total_cmp(b));
+        assert_eq!(input, expected);
+
+        // Descending through the extension trait
+        input.quick_sort_by(|a, b| b.total_cmp(a));
+        expected.reverse();
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn quick_sort_by_key_non_copy_records() {
+        #[derive(Debug, PartialEq)]
+        struct Batch {
+            id: u32,
+            samples: Vec<u8>,
+        }
+
+        let mut input: Vec<Batch> = (0..500u32)
+            .rev()
+            .map(|id| Batch {
+                id,
+                samples: vec![id as u8; 3],
+            })
+            .collect();
+
+        quick_sort_by_key(&mut input, |batch| batch.id);
+        assert!(input
+            .iter()
+            .enumerate()
+            .all(|(index, batch)| batch.id == index as u32));
+        assert_eq!(input[7].samples, vec![7; 3]);
+    }
 }
@@ -0,0 +1,321 @@
+//! Selection built on the `quick_sort` partition: find the k-th smallest element
+//! without sorting the whole slice.
+//!
+//! `select_nth` is an introselect. It partitions with the same median-of-three / ninther pivots
+//! as `quick_sort` and only keeps the side that contains `k`, which is expected O(n).
+//! A round that keeps more than 3/4 of the slice counts as bad. After a few bad rounds the
+//! pivots are switched to median-of-medians, which keeps at most ~70% every round.
+//! Every round then shrinks the slice geometrically except for a constant number of bad
+//! ones, each O(n), which bounds the worst case at O(n) as well.
+//!
+//! Afterwards `input[k]` holds the element a full sort would put there,
+//! everything before it is not greater and everything after it is not smaller.
+
+use super::quick_sort::{choose_pivot, partition3_by, sort_by_less};
+use std::cmp::Ordering;
+
+// Slices this short are sorted outright
+const SORT_THRESHOLD: usize = 16;
+
+// Rounds that keep more than 3/4 of the slice allowed before falling back to median-of-medians
+const BAD_ROUNDS: u32 = 4;
+
+/// Returns the k-th smallest element (0 based) and leaves it at `input[k]`.
+///
+/// Panics if `k` is out of bounds, just like indexing.
+pub fn select_nth<T: Ord>(input: &mut [T], k: usize) -> &mut T {
+    select_by_less(input, k, &mut |a: &T, b: &T| a < b);
+    &mut input[k]
+}
+
+pub fn select_nth_by<T, F>(input: &mut [T], k: usize, mut compare: F) -> &mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select_by_less(input, k, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+    &mut input[k]
+}
+
+/// The `k` largest elements, largest first, in O(n + k log k)
+pub fn top_k<T: Ord>(input: &mut [T], k: usize) -> &mut [T] {
+    let k = k.min(input.len());
+    let start = input.len() - k;
+
+    if k > 0 {
+        select_by_less(input, start, &mut |a: &T, b: &T| a < b);
+    }
+
+    let top = &mut input[start..];
+    sort_by_less(top, &mut |a: &T, b: &T| b < a);
+    top
+}
+
+/// The `k` smallest elements, smallest first, in O(n + k log k)
+pub fn bottom_k<T: Ord>(input: &mut [T], k: usize) -> &mut [T] {
+    let k = k.min(input.len());
+
+    if k > 0 {
+        select_by_less(input, k - 1, &mut |a: &T, b: &T| a < b);
+    }
+
+    let bottom = &mut input[..k];
+    sort_by_less(bottom, &mut |a: &T, b: &T| a < b);
+    bottom
+}
+
+/// Lower median, `None` for an empty slice
+pub fn median<T: Ord>(input: &mut [T]) -> Option<&T> {
+    percentile(input, 50.0)
+}
+
+/// Nearest-rank percentile: the smallest element that is greater than or equal to
+/// `percent` % of the input, e.g. `percentile(&mut latencies, 99.0)` for p99.
+/// `None` for an empty slice.
+///
+/// Panics if `percent` is not within 0..=100.
+pub fn percentile<T: Ord>(input: &mut [T], percent: f64) -> Option<&T> {
+    assert!(
+        (0.0..=100.0).contains(&percent),
+        "percentile must be within 0..=100, got {}",
+        percent
+    );
+
+    if input.is_empty() {
+        return None;
+    }
+
+    // Multiplying first keeps the product exact, `percent / 100.0` would round 7% up to
+    // 0.07000000000000001 and pick the 8th of 100 elements
+    let rank = (percent * input.len() as f64 / 100.0).ceil() as usize;
+    let k = rank.clamp(1, input.len()) - 1;
+    Some(&*select_nth(input, k))
+}
+
+fn select_by_less<T, F>(mut input: &mut [T], mut k: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    assert!(
+        k < input.len(),
+        "select index {} out of range for slice of length {}",
+        k,
+        input.len()
+    );
+
+    let mut bad_rounds = 0;
+
+    loop {
+        let slice_len = input.len();
+        if slice_len <= SORT_THRESHOLD {
+            sort_by_less(input, is_less);
+            return;
+        }
+
+        let pivot_index = if bad_rounds < BAD_ROUNDS {
+            choose_pivot(input, is_less)
+        } else {
+            median_of_medians(input, is_less)
+        };
+        input.swap(pivot_index, slice_len - 1);
+        let (lt, gt) = partition3_by(input, is_less);
+
+        let kept = if k < lt {
+            lt
+        } else if k >= gt {
+            slice_len - gt
+        } else {
+            0
+        };
+        if 4 * kept > 3 * slice_len {
+            bad_rounds += 1;
+        }
+
+        if k < lt {
+            input = &mut input[..lt];
+        } else if k >= gt {
+            input = &mut input[gt..];
+            k -= gt;
+        } else {
+            // k landed in the band of pivot-equal elements
+            return;
+        }
+    }
+}
+
+/// Returns the index of a pivot that is guaranteed to have at least ~30% of the slice
+/// on either side. The median of every group of five is moved to the front
+/// and we select the median of those.
+fn median_of_medians<T, F>(input: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let nf_groups = input.len().div_ceil(5);
+
+    for group in 0..nf_groups {
+        let start = 5 * group;
+        let end = (start + 5).min(input.len());
+        sort_by_less(&mut input[start..end], is_less);
+        input.swap(group, start + (end - start) / 2);
+    }
+
+    let mid = nf_groups / 2;
+    select_by_less(&mut input[..nf_groups], mid, is_less);
+    mid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    #[test]
+    fn select_nth_matches_sort() {
+        let mut rng = thread_rng();
+        let mut input: Vec<u32> = (0..10_000).map(|value| value % 1234).collect();
+        input.shuffle(&mut rng);
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        for k in [0, 1, 617, 5000, 9998, 9999] {
+            let mut scratch = input.clone();
+            assert_eq!(*select_nth(&mut scratch, k), sorted[k]);
+            assert!(scratch[..k].iter().all(|value| *value <= sorted[k]));
+            assert!(scratch[k + 1..].iter().all(|value| *value >= sorted[k]));
+        }
+    }
+
+    #[test]
+    fn select_nth_by_reverse() {
+        let mut input = vec![5.5, 1.25, 9.0, -3.0, 7.75];
+        assert_eq!(
+            *select_nth_by(&mut input, 0, |a: &f64, b| b.total_cmp(a)),
+            9.0
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_nth_out_of_range() {
+        select_nth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn median_of_medians_fallback() {
+        // The pivot alone must land in the middle ~40% no matter the input order
+        let mut input: Vec<i32> = (0..5000).rev().collect();
+        let pivot_index = median_of_medians(&mut input, &mut |a: &i32, b: &i32| a < b);
+        let pivot = input[pivot_index];
+        assert!((1500..3500).contains(&pivot));
+
+        let mut sorted_killer: Vec<i32> = (0..50_000).chain((0..50_000).rev()).collect();
+        assert_eq!(*select_nth(&mut sorted_killer, 50_000), 25_000);
+    }
+
+    #[test]
+    fn comparisons_stay_linear() {
+        use std::cell::Cell;
+
+        let n = 100_000;
+        let mut random: Vec<usize> = (0..n).collect();
+        random.shuffle(&mut thread_rng());
+        let inputs: Vec<Vec<usize>> = vec![
+            random,
+            (0..n).collect(),
+            (0..n).rev().collect(),
+            (0..n / 2).chain((0..n / 2).rev()).collect(),
+            (0..n).map(|value| value % 7).collect(),
+        ];
+
+        for input in inputs {
+            for k in [0, n / 3, n / 2, n - 1] {
+                let comparisons = Cell::new(0usize);
+                let mut scratch = input.clone();
+                select_nth_by(&mut scratch, k, |a, b| {
+                    comparisons.set(comparisons.get() + 1);
+                    a.cmp(b)
+                });
+                assert!(comparisons.get() < 20 * n);
+            }
+        }
+    }
+
+    #[test]
+    fn adversary_cannot_force_quadratic() {
+        use std::cell::RefCell;
+
+        // McIlroy's adversary: values stay undecided ("gas", above every decided value)
+        // until compared, and are then decided so the current pivot candidate ends up as
+        // small as possible. This makes every round with a sampled pivot a bad one,
+        // so only the switch to median-of-medians keeps the count linear.
+        struct Adversary {
+            values: Vec<usize>,
+            nf_solid: usize,
+            candidate: usize,
+            comparisons: usize,
+        }
+        let n = 100_000;
+        let gas = n;
+        let adversary = RefCell::new(Adversary {
+            values: vec![gas; n],
+            nf_solid: 0,
+            candidate: 0,
+            comparisons: 0,
+        });
+
+        let mut ids: Vec<usize> = (0..n).collect();
+        select_nth_by(&mut ids, n / 2, |&a, &b| {
+            let mut adversary = adversary.borrow_mut();
+            adversary.comparisons += 1;
+            if adversary.values[a] == gas && adversary.values[b] == gas {
+                let frozen = if a == adversary.candidate { a } else { b };
+                adversary.values[frozen] = adversary.nf_solid;
+                adversary.nf_solid += 1;
+            }
+            if adversary.values[a] == gas {
+                adversary.candidate = a;
+            } else if adversary.values[b] == gas {
+                adversary.candidate = b;
+            }
+            adversary.values[a].cmp(&adversary.values[b])
+        });
+
+        let adversary = adversary.into_inner();
+        assert!(adversary.comparisons < 20 * n);
+    }
+
+    #[test]
+    fn top_and_bottom_k() {
+        let mut input: Vec<i32> = (0..1000).collect();
+        input.shuffle(&mut thread_rng());
+
+        assert_eq!(top_k(&mut input, 3), &[999, 998, 997]);
+        assert_eq!(bottom_k(&mut input, 4), &[0, 1, 2, 3]);
+        assert_eq!(top_k(&mut input, 0), &[] as &[i32]);
+        assert_eq!(bottom_k(&mut [2, 1], 5), &[1, 2]);
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        let mut latencies: Vec<u32> = (1..=1000).collect();
+        latencies.shuffle(&mut thread_rng());
+
+        assert_eq!(median(&mut latencies), Some(&500));
+        assert_eq!(percentile(&mut latencies, 99.0), Some(&990));
+        assert_eq!(percentile(&mut latencies, 100.0), Some(&1000));
+        assert_eq!(percentile(&mut latencies, 0.0), Some(&1));
+        assert_eq!(median(&mut Vec::<u32>::new()), None);
+    }
+
+    #[test]
+    fn whole_percent_ranks() {
+        let mut values: Vec<u32> = (1..=100).collect();
+        values.shuffle(&mut thread_rng());
+
+        for percent in 1..=100 {
+            assert_eq!(percentile(&mut values, percent as f64), Some(&percent));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! A common interface over our sorting algorithms that can also report what they did.
+//!
+//! Passing a `SortStats` collects the number of comparisons, swaps and the deepest recursion,
+//! which lets tests assert complexity bounds like "at most c * n * log2(n) comparisons".
+//! Without stats the sort runs with the `()` sink, which compiles down to the plain sort.
+
+use super::quick_sort::sort_by_less_with_sink;
+use std::cmp::Ordering;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SortStats {
+    pub comparisons: u64,
+    pub swaps: u64,
+    pub max_depth: usize,
+}
+
+/// Receives events from the inside of a sort.
+/// Comparisons are counted by wrapping the comparator, so they do not show up here.
+pub trait StatsSink {
+    fn on_swap(&mut self) {}
+
+    /// Called whenever the sort enters a recursion level, the top level is depth 1
+    fn on_enter(&mut self, _depth: usize) {}
+}
+
+/// Ignores everything
+impl StatsSink for () {}
+
+impl StatsSink for SortStats {
+    fn on_swap(&mut self) {
+        self.swaps += 1;
+    }
+
+    fn on_enter(&mut self, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+    }
+}
+
+pub trait Sorter {
+    /// Sorts `input` by `compare`, adding what happened to `stats` if given.
+    /// Counts are added to what `stats` already holds, so one `SortStats` can
+    /// accumulate several sorts.
+    fn sort_by_with_stats<T, F>(&self, input: &mut [T], compare: F, stats: Option<&mut SortStats>)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    fn sort_by<T, F>(&self, input: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_by_with_stats(input, compare, None);
+    }
+
+    fn sort<T: Ord>(&self, input: &mut [T]) {
+        self.sort_by(input, T::cmp);
+    }
+
+    fn sort_with_stats<T: Ord>(&self, input: &mut [T], stats: &mut SortStats) {
+        self.sort_by_with_stats(input, T::cmp, Some(stats));
+    }
+}
+
+/// The introsort from `quick_sort`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct QuickSort;
+
+impl Sorter for QuickSort {
+    fn sort_by_with_stats<T, F>(
+        &self,
+        input: &mut [T],
+        mut compare: F,
+        stats: Option<&mut SortStats>,
+    ) where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        match stats {
+            None => {
+                sort_by_less_with_sink(
+                    input,
+                    &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+                    &mut (),
+                );
+            }
+            Some(stats) => {
+                let mut comparisons = 0;
+                sort_by_less_with_sink(
+                    input,
+                    &mut |a: &T, b: &T| {
+                        comparisons += 1;
+                        compare(a, b) == Ordering::Less
+                    },
+                    stats,
+                );
+                stats.comparisons += comparisons;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+
+    const LEN: usize = 100_000;
+
+    fn n_log_n() -> f64 {
+        LEN as f64 * (LEN as f64).log2()
+    }
+
+    fn inputs() -> Vec<(&'static str, Vec<u32>)> {
+        let sorted: Vec<u32> = (0..LEN as u32).collect();
+        let mut random = sorted.clone();
+        random.shuffle(&mut thread_rng());
+
+        vec![
+            ("random", random),
+            ("reversed", sorted.iter().rev().copied().collect()),
+            ("all equal", vec![7; LEN]),
+            ("few keys", (0..LEN as u32).map(|value| value % 3).collect()),
+            ("sorted", sorted),
+        ]
+    }
+
+    #[test]
+    fn quick_sort_sorts() {
+        let mut input = vec![3, 1, 2];
+        QuickSort.sort(&mut input);
+        assert_eq!(input, vec![1, 2, 3]);
+
+        QuickSort.sort_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn quick_sort_complexity_bounds() {
+        for (name, mut input) in inputs() {
+            let mut stats = SortStats::default();
+            QuickSort.sort_with_stats(&mut input, &mut stats);
+
+            assert!(input.windows(2).all(|pair| pair[0] <= pair[1]), "{}", name);
+            assert!(
+                (stats.comparisons as f64) <= 3.0 * n_log_n(),
+                "{}: {:?}",
+                name,
+                stats
+            );
+            assert!(
+                (stats.swaps as f64) <= 3.0 * n_log_n(),
+                "{}: {:?}",
+                name,
+                stats
+            );
+            // We only recurse into the smaller half
+            assert!(
+                stats.max_depth <= LEN.ilog2() as usize + 1,
+                "{}: {:?}",
+                name,
+                stats
+            );
+        }
+    }
+
+    #[test]
+    fn stats_accumulate() {
+        let mut stats = SortStats::default();
+        QuickSort.sort_with_stats(&mut [2, 1], &mut stats);
+        let after_one = stats;
+        QuickSort.sort_with_stats(&mut [2, 1], &mut stats);
+
+        assert_eq!(after_one.comparisons, 1);
+        assert_eq!(after_one.swaps, 1);
+        assert_eq!(stats.comparisons, 2);
+        assert_eq!(stats.max_depth, 1);
+    }
+}
@@ -0,0 +1,441 @@
+//! Aho-Corasick: every pattern found by scanning the haystack forward. Overlapping matching
+//! is a single pass, the leftmost kinds may read bytes again after a match, see below.
+//!
+//! The patterns go into a `TrieMap<usize, Bytes>` (pattern -> id), which is then laid out
+//! breadth first as a table of states, one per trie node, with two extra links:
+//! - failure link: the state of the longest proper suffix of this state's string that is
+//!   still in the trie, where the scan continues when no child matches the next byte
+//! - output link: the nearest state on the failure chain that ends a pattern, so all
+//!   patterns ending at a position are listed without walking the whole failure chain
+//!
+//! The leftmost kinds need an automaton of their own. Once a match has been seen, following
+//! a failure link could only lead to matches starting later, so every state whose trie path
+//! passes a pattern fails into a dead state that ends the scan instead. For leftmost-first
+//! a pattern that has an earlier pattern as a prefix can never win and is left out.
+//! The match to report is then simply the last one seen before the scan dies.
+//!
+//! `find_iter` is lazy and reports `(pattern_id, start, end)` with byte offsets, `end`
+//! exclusive. Overlapping matching reads every byte once, O(n + matches). The leftmost kinds
+//! resume right after a match, so bytes read past its end while a match that started earlier
+//! could still turn up are read again: the worst case is O(n * longest pattern) for both.
+//! E.g. `["a", "aaaab"]` leftmost-longest on a run of `a`, or `["b", "ababc"]` leftmost-first
+//! on a run of `ab`. Leftmost-first never looks ahead past a match on its own pattern path
+//! though, so `["a", "aaaab"]` leftmost-first on a run of `a` reads every byte once.
+
+use super::alphabet::{Bytes, Links};
+use super::trie_map::TrieMap;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchKind {
+    /// Every occurrence of every pattern, ordered by end, longest first on the same end
+    #[default]
+    Overlapping,
+    /// Non-overlapping, the match starting first wins, on the same start the pattern given first
+    LeftmostFirst,
+    /// Non-overlapping, the match starting first wins, on the same start the longest one
+    LeftmostLongest,
+}
+
+// Ends a leftmost scan, every byte leads back to it
+const DEAD: usize = 0;
+const ROOT: usize = 1;
+
+struct State {
+    // Sorted by byte
+    transitions: Vec<(u8, usize)>,
+    failure: usize,
+    // The pattern ending exactly here
+    pattern: Option<usize>,
+    // The next state on the failure chain with a pattern
+    output: Option<usize>,
+    depth: usize,
+}
+
+// Where `byte` leads from `state` without failing over, the root and the dead state never fail
+fn follow(states: &[State], state: usize, byte: u8) -> Option<usize> {
+    if state == DEAD {
+        return Some(DEAD);
+    }
+    let transition = states[state]
+        .transitions
+        .binary_search_by(|(other, _)| other.cmp(&byte))
+        .ok()
+        .map(|index| states[state].transitions[index].1);
+    match (transition, state) {
+        (None, ROOT) => Some(ROOT),
+        _ => transition,
+    }
+}
+
+pub struct AhoCorasick {
+    states: Vec<State>,
+    nf_patterns: usize,
+    kind: MatchKind,
+}
+
+impl AhoCorasick {
+    /// Reports overlapping matches, see `with_match_kind`
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        Self::with_match_kind(patterns, MatchKind::default())
+    }
+
+    /// Pattern ids are the positions in `patterns`. A pattern given twice is reported with
+    /// its first id, an empty pattern never matches.
+    pub fn with_match_kind<I, P>(patterns: I, kind: MatchKind) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut trie: TrieMap<usize, Bytes> = TrieMap::new();
+        let mut nf_patterns = 0;
+        for (id, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            nf_patterns += 1;
+            if pattern.is_empty() || trie.contains_key(pattern) {
+                continue;
+            }
+            if kind == MatchKind::LeftmostFirst && has_pattern_prefix(&trie, pattern) {
+                continue;
+            }
+            trie.insert(pattern, id).unwrap();
+        }
+
+        // Number the trie nodes breadth first, so a state comes after its failure state.
+        // Also note which states have a pattern on their trie path, the root excluded.
+        let mut states = vec![State {
+            transitions: Vec::new(),
+            failure: DEAD,
+            pattern: None,
+            output: None,
+            depth: 0,
+        }];
+        let mut after_match = Vec::new();
+        let mut queue = VecDeque::from([(trie.root(), 0, false)]);
+        while let Some((node, depth, matched)) = queue.pop_front() {
+            let first_child = states.len() + queue.len() + 1;
+            let transitions = node
+                .links
+                .iter()
+                .enumerate()
+                .map(|(index, (byte, _))| (byte, first_child + index))
+                .collect();
+            states.push(State {
+                transitions,
+                failure: ROOT,
+                pattern: node.value,
+                output: None,
+                depth,
+            });
+            after_match.push(matched);
+            queue.extend(node.links.iter().map(|(_, child)| {
+                let matched = matched || child.value.is_some();
+                (child, depth + 1, matched)
+            }));
+        }
+
+        for parent in ROOT..states.len() {
+            for index in 0..states[parent].transitions.len() {
+                let (byte, child) = states[parent].transitions[index];
+                let failure = if kind != MatchKind::Overlapping && after_match[child - ROOT] {
+                    DEAD
+                } else if parent == ROOT {
+                    ROOT
+                } else {
+                    let mut state = states[parent].failure;
+                    loop {
+                        if let Some(next) = follow(&states, state, byte) {
+                            break next;
+                        }
+                        state = states[state].failure;
+                    }
+                };
+                states[child].failure = failure;
+                states[child].output = match states[failure].pattern {
+                    Some(_) => Some(failure),
+                    None => states[failure].output,
+                };
+            }
+        }
+
+        Self {
+            states,
+            nf_patterns,
+            kind,
+        }
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    pub fn nf_patterns(&self) -> usize {
+        self.nf_patterns
+    }
+
+    /// Lazily yields `(pattern_id, start, end)` for the matches in `haystack`
+    pub fn find_iter<'a, H: AsRef<[u8]> + ?Sized>(&'a self, haystack: &'a H) -> FindIter<'a> {
+        FindIter {
+            automaton: self,
+            haystack: haystack.as_ref(),
+            position: 0,
+            state: ROOT,
+            pending: None,
+        }
+    }
+
+    pub fn is_match<H: AsRef<[u8]> + ?Sized>(&self, haystack: &H) -> bool {
+        self.find_iter(haystack).next().is_some()
+    }
+
+    // Follows failure links until `byte` can be read
+    fn next_state(&self, mut state: usize, byte: u8) -> usize {
+        loop {
+            if let Some(next) = follow(&self.states, state, byte) {
+                return next;
+            }
+            state = self.states[state].failure;
+        }
+    }
+
+    // The state itself if it ends a pattern, else its output link
+    fn first_output(&self, state: usize) -> Option<usize> {
+        match self.states[state].pattern {
+            Some(_) => Some(state),
+            None => self.states[state].output,
+        }
+    }
+
+    // `(pattern_id, start, end)` for a match of the pattern of `state` ending at `end`
+    fn found(&self, state: usize, end: usize) -> (usize, usize, usize) {
+        let state = &self.states[state];
+        (state.pattern.unwrap(), end - state.depth, end)
+    }
+}
+
+// Whether a proper prefix of `pattern` is already a pattern
+fn has_pattern_prefix(trie: &TrieMap<usize, Bytes>, pattern: &[u8]) -> bool {
+    let mut node = trie.root();
+    for &byte in &pattern[..pattern.len() - 1] {
+        match node.links.get(byte) {
+            Some(child) if child.value.is_some() => return true,
+            Some(child) => node = child,
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Iterator over the matches in a haystack, see `AhoCorasick::find_iter`
+pub struct FindIter<'a> {
+    automaton: &'a AhoCorasick,
+    haystack: &'a [u8],
+    // Next byte to read
+    position: usize,
+    state: usize,
+    // Overlapping only: the next state on the output chain still to report
+    pending: Option<usize>,
+}
+
+impl FindIter<'_> {
+    fn next_overlapping(&mut self) -> Option<(usize, usize, usize)> {
+        loop {
+            if let Some(state) = self.pending {
+                self.pending = self.automaton.states[state].output;
+                return Some(self.automaton.found(state, self.position));
+            }
+
+            let &byte = self.haystack.get(self.position)?;
+            self.state = self.automaton.next_state(self.state, byte);
+            self.position += 1;
+            self.pending = self.automaton.first_output(self.state);
+        }
+    }
+
+    fn next_leftmost(&mut self) -> Option<(usize, usize, usize)> {
+        let automaton = self.automaton;
+        let mut last = None;
+
+        while let Some(&byte) = self.haystack.get(self.position) {
+            self.state = automaton.next_state(self.state, byte);
+            self.position += 1;
+            if self.state == DEAD {
+                break;
+            }
+            // The longest pattern ending here, it starts the earliest
+            if let Some(state) = automaton.first_output(self.state) {
+                last = Some(automaton.found(state, self.position));
+            }
+        }
+
+        // Matches don't overlap: go on right after this one, from scratch
+        let (_, _, end) = last?;
+        self.position = end;
+        self.state = ROOT;
+        last
+    }
+}
+
+impl Iterator for FindIter<'_> {
+    type Item = (usize, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.automaton.kind {
+            MatchKind::Overlapping => self.next_overlapping(),
+            MatchKind::LeftmostFirst | MatchKind::LeftmostLongest => self.next_leftmost(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_all(automaton: &AhoCorasick, haystack: &str) -> Vec<(usize, usize, usize)> {
+        automaton.find_iter(haystack).collect()
+    }
+
+    #[test]
+    fn overlapping_textbook() {
+        let automaton = AhoCorasick::new(["he", "she", "his", "hers"]);
+
+        // "ushers": she and he end together, the longer comes first
+        assert_eq!(
+            find_all(&automaton, "ushers"),
+            vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]
+        );
+        assert_eq!(
+            find_all(&automaton, "ahishe"),
+            vec![(2, 1, 4), (1, 3, 6), (0, 4, 6)]
+        );
+        assert!(find_all(&automaton, "xyz").is_empty());
+        assert!(automaton.is_match("this"));
+    }
+
+    #[test]
+    fn leftmost_first_and_longest() {
+        let patterns = ["Sam", "Samwise", "wise"];
+        let first = AhoCorasick::with_match_kind(patterns, MatchKind::LeftmostFirst);
+        let longest = AhoCorasick::with_match_kind(patterns, MatchKind::LeftmostLongest);
+
+        assert_eq!(find_all(&first, "Samwise"), vec![(0, 0, 3), (2, 3, 7)]);
+        assert_eq!(find_all(&longest, "Samwise"), vec![(1, 0, 7)]);
+
+        // Order of the patterns decides for leftmost-first only
+        let patterns = ["abcd", "ab", "bcde"];
+        let first = AhoCorasick::with_match_kind(patterns, MatchKind::LeftmostFirst);
+        let longest = AhoCorasick::with_match_kind(patterns, MatchKind::LeftmostLongest);
+        assert_eq!(find_all(&first, "xabcde"), vec![(0, 1, 5)]);
+        assert_eq!(find_all(&longest, "xabcde"), vec![(0, 1, 5)]);
+        assert_eq!(find_all(&first, "abab"), vec![(1, 0, 2), (1, 2, 4)]);
+    }
+
+    #[test]
+    fn duplicate_and_empty_patterns() {
+        let automaton = AhoCorasick::new(["", "ab", "ab", "b"]);
+
+        assert_eq!(automaton.nf_patterns(), 4);
+        assert_eq!(find_all(&automaton, "ab"), vec![(1, 0, 2), (3, 1, 2)]);
+    }
+
+    #[test]
+    fn streams_lazily() {
+        let automaton = AhoCorasick::new([b"\xff\x00".as_slice()]);
+        let haystack: Vec<u8> = [0xff, 0x00].repeat(1_000_000);
+
+        let mut matches = automaton.find_iter(&haystack);
+        assert_eq!(matches.next(), Some((0, 0, 2)));
+        assert_eq!(matches.next(), Some((0, 2, 4)));
+    }
+
+    #[test]
+    fn leftmost_first_never_reads_back() {
+        let long = format!("{}b", "a".repeat(1000));
+        let automaton =
+            AhoCorasick::with_match_kind(["a", long.as_str()], MatchKind::LeftmostFirst);
+        // "a" always wins over the long pattern, which is left out altogether
+        assert_eq!(automaton.states.len(), 3);
+
+        let haystack = "a".repeat(10_000);
+        let mut matches = automaton.find_iter(&haystack);
+        for start in 0..haystack.len() {
+            assert_eq!(matches.next(), Some((0, start, start + 1)));
+            // Nothing was read past the match, so nothing is read twice
+            assert_eq!(matches.position, start + 1);
+        }
+        assert_eq!(matches.next(), None);
+
+        // Leftmost-longest has to look ahead for the long pattern
+        let automaton =
+            AhoCorasick::with_match_kind(["a", long.as_str()], MatchKind::LeftmostLongest);
+        assert_eq!(automaton.find_iter(&haystack).count(), haystack.len());
+        assert_eq!(
+            automaton.find_iter(&format!("a{long}")).collect::<Vec<_>>(),
+            vec![(0, 0, 1), (1, 1, 1002)]
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+        let random_text = |rng: &mut rand::rngs::ThreadRng, length: usize| -> Vec<u8> {
+            (0..length).map(|_| rng.gen_range(b'a'..b'd')).collect()
+        };
+
+        for _ in 0..50 {
+            let patterns: Vec<Vec<u8>> = (0..rng.gen_range(1..20))
+                .map(|_| {
+                    let length = rng.gen_range(1..5);
+                    random_text(&mut rng, length)
+                })
+                .collect();
+            let haystack = random_text(&mut rng, 200);
+
+            // First id per distinct pattern
+            let id_of = |pattern: &[u8]| patterns.iter().position(|other| other == pattern);
+            let mut all: Vec<(usize, usize, usize)> = Vec::new();
+            for start in 0..haystack.len() {
+                for end in start + 1..=haystack.len() {
+                    if let Some(id) = id_of(&haystack[start..end]) {
+                        all.push((id, start, end));
+                    }
+                }
+            }
+
+            let overlapping = AhoCorasick::new(&patterns);
+            let mut found: Vec<_> = overlapping.find_iter(&haystack).collect();
+            found.sort();
+            let mut expected = all.clone();
+            expected.sort();
+            assert_eq!(found, expected);
+
+            for kind in [MatchKind::LeftmostFirst, MatchKind::LeftmostLongest] {
+                let mut expected = Vec::new();
+                let mut position = 0;
+                loop {
+                    let best = all
+                        .iter()
+                        .filter(|&&(_, start, _)| start >= position)
+                        .min_by_key(|&&(id, start, end)| match kind {
+                            MatchKind::LeftmostFirst => (start, id),
+                            _ => (start, usize::MAX - end),
+                        });
+                    let Some(&(id, start, end)) = best else {
+                        break;
+                    };
+                    expected.push((id, start, end));
+                    position = end;
+                }
+
+                let automaton = AhoCorasick::with_match_kind(&patterns, kind);
+                assert_eq!(automaton.find_iter(&haystack).collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+}
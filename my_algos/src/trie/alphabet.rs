@@ -0,0 +1,245 @@
+//! What a trie is keyed by and how a node stores its children.
+//!
+//! An `Alphabet` splits a key into symbols and picks the child storage (`Links`):
+//! - `Lowercase`: 'a'..='z' in a fixed array of 26 links, O(1) per step but 26 pointers a node
+//! - `Unicode`: any `char`, children in a sorted vec, binary searched
+//! - `Bytes`: any byte string, children in a sorted vec
+//!
+//! The sparse links only pay for the children that exist, which is what large alphabets need.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrieError {
+    /// The key contains a character the alphabet can't store, `position` is its byte offset
+    UnsupportedCharacter { character: char, position: usize },
+}
+
+impl fmt::Display for TrieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrieError::UnsupportedCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "unsupported character {:?} at byte {}",
+                character, position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TrieError {}
+
+/// Children of a trie node, keyed by symbol
+pub trait Links<S, T>: Default {
+    fn get(&self, symbol: S) -> Option<&T>;
+
+    fn get_mut(&mut self, symbol: S) -> Option<&mut T>;
+
+    fn get_or_insert_with<F: FnOnce() -> T>(&mut self, symbol: S, make: F) -> &mut T;
+
+    fn remove(&mut self, symbol: S) -> Option<T>;
+
+    /// Children in ascending symbol order
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (S, &'a T)>
+    where
+        T: 'a;
+
+    fn len(&self) -> usize;
+
+    /// Heap bytes of the link storage itself, not counting the children
+    fn allocated_bytes(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub trait Alphabet {
+    type Symbol: Copy + Ord + fmt::Debug;
+    /// What keys are borrowed as, `str` or `[u8]`
+    type Key: ?Sized;
+    /// What iterators hand keys back as, `String` or `Vec<u8>`
+    type OwnedKey;
+    type Links<T>: Links<Self::Symbol, T>;
+
+    /// Splits the key into symbols, an error for anything the alphabet can't store
+    fn symbols(key: &Self::Key) -> impl Iterator<Item = Result<Self::Symbol, TrieError>>;
+
+    /// Puts a key back together from its symbols
+    fn to_key(symbols: &[Self::Symbol]) -> Self::OwnedKey;
+}
+
+// 'a'..='z'
+const SIZE: usize = 26;
+
+/// One slot per letter, the original layout of `Trie`
+pub struct ArrayLinks<T>([Option<Box<T>>; SIZE]);
+
+impl<T> Default for ArrayLinks<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<T> Links<u8, T> for ArrayLinks<T> {
+    fn get(&self, symbol: u8) -> Option<&T> {
+        self.0[symbol as usize].as_deref()
+    }
+
+    fn get_mut(&mut self, symbol: u8) -> Option<&mut T> {
+        self.0[symbol as usize].as_deref_mut()
+    }
+
+    fn get_or_insert_with<F: FnOnce() -> T>(&mut self, symbol: u8, make: F) -> &mut T {
+        self.0[symbol as usize].get_or_insert_with(|| Box::new(make()))
+    }
+
+    fn remove(&mut self, symbol: u8) -> Option<T> {
+        self.0[symbol as usize].take().map(|child| *child)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (u8, &'a T)>
+    where
+        T: 'a,
+    {
+        (0..SIZE as u8)
+            .zip(&self.0)
+            .filter_map(|(symbol, child)| Some((symbol, child.as_deref()?)))
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().filter(|child| child.is_some()).count()
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        // The slots live inside the node
+        0
+    }
+}
+
+/// Only the children that exist, sorted by symbol
+pub struct SparseLinks<S, T>(Vec<(S, Box<T>)>);
+
+impl<S, T> Default for SparseLinks<S, T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<S: Copy + Ord, T> SparseLinks<S, T> {
+    fn position(&self, symbol: S) -> Result<usize, usize> {
+        self.0.binary_search_by(|(other, _)| other.cmp(&symbol))
+    }
+}
+
+impl<S: Copy + Ord, T> Links<S, T> for SparseLinks<S, T> {
+    fn get(&self, symbol: S) -> Option<&T> {
+        let index = self.position(symbol).ok()?;
+        Some(&self.0[index].1)
+    }
+
+    fn get_mut(&mut self, symbol: S) -> Option<&mut T> {
+        let index = self.position(symbol).ok()?;
+        Some(&mut self.0[index].1)
+    }
+
+    fn get_or_insert_with<F: FnOnce() -> T>(&mut self, symbol: S, make: F) -> &mut T {
+        let index = match self.position(symbol) {
+            Ok(index) => index,
+            Err(index) => {
+                self.0.insert(index, (symbol, Box::new(make())));
+                index
+            }
+        };
+        &mut self.0[index].1
+    }
+
+    fn remove(&mut self, symbol: S) -> Option<T> {
+        let index = self.position(symbol).ok()?;
+        Some(*self.0.remove(index).1)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (S, &'a T)>
+    where
+        T: 'a,
+    {
+        self.0.iter().map(|(symbol, child)| (*symbol, &**child))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.0.capacity() * std::mem::size_of::<(S, Box<T>)>()
+    }
+}
+
+/// 'a'..='z' only, symbols are 0..26
+pub struct Lowercase;
+
+impl Alphabet for Lowercase {
+    type Symbol = u8;
+    type Key = str;
+    type OwnedKey = String;
+    type Links<T> = ArrayLinks<T>;
+
+    fn symbols(key: &str) -> impl Iterator<Item = Result<u8, TrieError>> {
+        key.char_indices().map(|(position, character)| {
+            if character.is_ascii_lowercase() {
+                Ok(character as u8 - b'a')
+            } else {
+                Err(TrieError::UnsupportedCharacter {
+                    character,
+                    position,
+                })
+            }
+        })
+    }
+
+    fn to_key(symbols: &[u8]) -> String {
+        symbols
+            .iter()
+            .map(|&symbol| (b'a' + symbol) as char)
+            .collect()
+    }
+}
+
+/// Any `char`
+pub struct Unicode;
+
+impl Alphabet for Unicode {
+    type Symbol = char;
+    type Key = str;
+    type OwnedKey = String;
+    type Links<T> = SparseLinks<char, T>;
+
+    fn symbols(key: &str) -> impl Iterator<Item = Result<char, TrieError>> {
+        key.chars().map(Ok)
+    }
+
+    fn to_key(symbols: &[char]) -> String {
+        symbols.iter().collect()
+    }
+}
+
+/// Any byte string, not necessarily UTF-8
+pub struct Bytes;
+
+impl Alphabet for Bytes {
+    type Symbol = u8;
+    type Key = [u8];
+    type OwnedKey = Vec<u8>;
+    type Links<T> = SparseLinks<u8, T>;
+
+    fn symbols(key: &[u8]) -> impl Iterator<Item = Result<u8, TrieError>> {
+        key.iter().copied().map(Ok)
+    }
+
+    fn to_key(symbols: &[u8]) -> Vec<u8> {
+        symbols.to_vec()
+    }
+}
@@ -0,0 +1,346 @@
+//! Ranked prefix completion: the k most popular words under a prefix.
+//!
+//! The words and their weights live in a `TrieMap` whose nodes cache `Best`, the highest
+//! weight anywhere in their subtree. `complete` is then a best-first search over a max heap:
+//! a node goes in with its best weight, a word with its weight. Nothing in a subtree can beat
+//! the subtree's best, so once k words came out of the heap they are the top k. The work
+//! depends on k, the prefix length and the fanout of the nodes on the way, not on the
+//! subtree size.
+//!
+//! Paths are not copied into the heap. Every visited node gets one entry in an arena,
+//! its symbol and the entry of its parent, and a word is only put together when it comes out.
+//!
+//! `bump` changes one weight and the map recomputes `Best` on the path back to the root,
+//! O(|word| * fanout).
+
+use super::alphabet::{Alphabet, Links, Lowercase, TrieError};
+use super::trie_map::{Node, Summary, TrieMap};
+use crate::heap::d_ary_heap::DAryHeap;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+/// Highest weight in a subtree, 0 for an empty one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Best(pub u64);
+
+impl Summary<u64> for Best {
+    fn summarize(weight: Option<&u64>, children: impl Iterator<Item = Self>) -> Self {
+        let children = children.map(|Best(best)| best);
+        Best(children.chain(weight.copied()).max().unwrap_or(0))
+    }
+}
+
+/// Words with weights, completed by weight, highest first
+pub struct Autocomplete<A: Alphabet = Lowercase> {
+    weights: TrieMap<u64, A, Best>,
+}
+
+impl<A: Alphabet> Default for Autocomplete<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Alphabet> Autocomplete<A> {
+    pub fn new() -> Self {
+        Self {
+            weights: TrieMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Sets the weight of a word, returns the old one if it was there
+    pub fn insert(&mut self, word: &A::Key, weight: u64) -> Result<Option<u64>, TrieError> {
+        self.weights.insert(word, weight)
+    }
+
+    /// Adds `delta` to the weight of a word, a new word starts at 0.
+    /// Returns the new weight.
+    pub fn bump(&mut self, word: &A::Key, delta: u64) -> Result<u64, TrieError> {
+        let weight = self.weight(word).unwrap_or(0).saturating_add(delta);
+        self.weights.insert(word, weight)?;
+        Ok(weight)
+    }
+
+    /// Takes the word out, returns its weight if it was there
+    pub fn remove(&mut self, word: &A::Key) -> Option<u64> {
+        self.weights.remove(word)
+    }
+
+    pub fn weight(&self, word: &A::Key) -> Option<u64> {
+        self.weights.get(word).copied()
+    }
+
+    /// The `k` highest weighted words starting with `prefix`, highest first.
+    /// Equal weights come out in lexicographic order.
+    pub fn complete(&self, prefix: &A::Key, k: usize) -> Vec<(A::OwnedKey, u64)> {
+        let mut completions = Vec::with_capacity(k);
+        let (Some(start), Ok(prefix)) = (
+            self.weights.node(prefix),
+            A::symbols(prefix).collect::<Result<Vec<_>, _>>(),
+        ) else {
+            return completions;
+        };
+
+        // Entry 0 stands for the prefix itself
+        let arena = RefCell::new(vec![Step {
+            symbol: None,
+            parent: 0,
+            depth: 0,
+        }]);
+        let mut heap = DAryHeap::new_max(4);
+        heap.push(Candidate {
+            priority: start.summary.0,
+            step: 0,
+            word: false,
+            node: start,
+            arena: &arena,
+        });
+
+        while completions.len() < k {
+            let Some(candidate) = heap.pop() else {
+                break;
+            };
+            if candidate.word {
+                let mut word = prefix.clone();
+                word.extend(path(&arena.borrow(), candidate.step));
+                completions.push((A::to_key(&word), candidate.priority));
+                continue;
+            }
+
+            let node = candidate.node;
+            if let Some(&weight) = node.value.as_ref() {
+                heap.push(Candidate {
+                    priority: weight,
+                    word: true,
+                    ..candidate
+                });
+            }
+            for (symbol, child) in node.links.iter() {
+                let step = {
+                    let mut arena = arena.borrow_mut();
+                    let depth = arena[candidate.step].depth + 1;
+                    arena.push(Step {
+                        symbol: Some(symbol),
+                        parent: candidate.step,
+                        depth,
+                    });
+                    arena.len() - 1
+                };
+                heap.push(Candidate {
+                    priority: child.summary.0,
+                    step,
+                    word: false,
+                    node: child,
+                    arena: &arena,
+                });
+            }
+        }
+
+        completions
+    }
+}
+
+// A node reached by `complete`, below the prefix
+struct Step<S> {
+    // `None` only for the prefix itself
+    symbol: Option<S>,
+    parent: usize,
+    depth: usize,
+}
+
+// The symbols from the prefix down to `step`
+fn path<S: Copy>(arena: &[Step<S>], mut step: usize) -> Vec<S> {
+    let mut symbols = Vec::with_capacity(arena[step].depth);
+    while let Some(symbol) = arena[step].symbol {
+        symbols.push(symbol);
+        step = arena[step].parent;
+    }
+    symbols.reverse();
+    symbols
+}
+
+// Lexicographic order of the paths to two steps, a path before the paths it prefixes
+fn compare_paths<S: Ord>(arena: &[Step<S>], mut a: usize, mut b: usize) -> Ordering {
+    let by_depth = arena[a].depth.cmp(&arena[b].depth);
+    while arena[a].depth > arena[b].depth {
+        a = arena[a].parent;
+    }
+    while arena[b].depth > arena[a].depth {
+        b = arena[b].parent;
+    }
+    if a == b {
+        return by_depth;
+    }
+    // Climb to the children of the last common node, their symbols decide
+    while arena[a].parent != arena[b].parent {
+        a = arena[a].parent;
+        b = arena[b].parent;
+    }
+    arena[a].symbol.cmp(&arena[b].symbol)
+}
+
+// A subtree (`word == false`) or a single word waiting in the heap of `complete`
+struct Candidate<'a, A: Alphabet> {
+    priority: u64,
+    // Where the path to `node` ends in the arena
+    step: usize,
+    // A word beats the subtree it ends in, so it comes out first on a tie
+    word: bool,
+    node: &'a Node<u64, A, Best>,
+    arena: &'a RefCell<Vec<Step<A::Symbol>>>,
+}
+
+impl<A: Alphabet> PartialEq for Candidate<'_, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<A: Alphabet> Eq for Candidate<'_, A> {}
+
+impl<A: Alphabet> PartialOrd for Candidate<'_, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Alphabet> Ord for Candidate<'_, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // On equal priority the smaller path is the greater candidate, so it comes out first
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| compare_paths(&self.arena.borrow(), other.step, self.step))
+            .then_with(|| self.word.cmp(&other.word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::alphabet::Unicode;
+
+    fn search_box() -> Autocomplete {
+        let mut completions = Autocomplete::new();
+        for (word, weight) in [
+            ("rust", 90),
+            ("rustacean", 15),
+            ("rustup", 40),
+            ("ruby", 60),
+            ("run", 75),
+            ("rune", 5),
+            ("python", 80),
+        ] {
+            completions.insert(word, weight).unwrap();
+        }
+        completions
+    }
+
+    fn words(completions: Vec<(String, u64)>) -> Vec<String> {
+        completions.into_iter().map(|(word, _)| word).collect()
+    }
+
+    #[test]
+    fn top_k_under_prefix() {
+        let completions = search_box();
+
+        assert_eq!(
+            completions.complete("ru", 3),
+            vec![
+                ("rust".to_string(), 90),
+                ("run".to_string(), 75),
+                ("ruby".to_string(), 60)
+            ]
+        );
+        assert_eq!(
+            words(completions.complete("rust", 10)),
+            vec!["rust", "rustup", "rustacean"]
+        );
+        assert_eq!(words(completions.complete("", 1)), vec!["rust"]);
+        assert!(completions.complete("java", 3).is_empty());
+        assert!(completions.complete("Ru", 3).is_empty());
+        assert!(completions.complete("ru", 0).is_empty());
+    }
+
+    #[test]
+    fn bump_reorders() {
+        let mut completions = search_box();
+
+        assert_eq!(completions.bump("rune", 100), Ok(105));
+        assert_eq!(words(completions.complete("ru", 2)), vec!["rune", "rust"]);
+
+        // Bumping a new word adds it
+        assert_eq!(completions.bump("rustc", 1), Ok(1));
+        assert_eq!(completions.len(), 8);
+        assert_eq!(completions.weight("rustc"), Some(1));
+
+        // Lowering a weight through insert also lowers the cached best on the path
+        completions.insert("rune", 0).unwrap();
+        assert_eq!(words(completions.complete("run", 1)), vec!["run"]);
+    }
+
+    #[test]
+    fn remove_updates_best() {
+        let mut completions = search_box();
+
+        assert_eq!(completions.remove("rust"), Some(90));
+        assert_eq!(completions.remove("rust"), None);
+        assert_eq!(completions.len(), 6);
+        // The subtree under "rust" lost its best word but keeps the others
+        assert_eq!(
+            completions.complete("rus", 5),
+            vec![("rustup".to_string(), 40), ("rustacean".to_string(), 15)]
+        );
+        assert_eq!(words(completions.complete("", 1)), vec!["python"]);
+    }
+
+    #[test]
+    fn ties_are_lexicographic() {
+        let mut completions: Autocomplete<Unicode> = Autocomplete::new();
+        for word in ["cab", "ca", "cb", "c"] {
+            completions.insert(word, 7).unwrap();
+        }
+
+        assert_eq!(
+            words(completions.complete("c", 4)),
+            vec!["c", "ca", "cab", "cb"]
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+        let mut completions: Autocomplete = Autocomplete::new();
+        let mut all: Vec<(String, u64)> = Vec::new();
+        for _ in 0..500 {
+            let length = rng.gen_range(1..6);
+            let word: String = (0..length)
+                .map(|_| rng.gen_range(b'a'..b'e') as char)
+                .collect();
+            let weight = completions.bump(&word, rng.gen_range(1..100)).unwrap();
+            all.retain(|(other, _)| *other != word);
+            all.push((word, weight));
+        }
+
+        for prefix in ["", "a", "bc", "dd"] {
+            let mut expected: Vec<(String, u64)> = all
+                .iter()
+                .filter(|(word, _)| word.starts_with(prefix))
+                .cloned()
+                .collect();
+            expected.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            expected.truncate(10);
+
+            assert_eq!(completions.complete(prefix, 10), expected);
+        }
+    }
+}
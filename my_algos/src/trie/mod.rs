@@ -0,0 +1,214 @@
+pub mod aho_corasick;
+pub mod alphabet;
+pub mod autocomplete;
+pub mod radix_trie;
+pub mod search;
+pub mod trie_map;
+
+use alphabet::{Alphabet, Lowercase, TrieError};
+use trie_map::TrieMap;
+
+/// Set of words over an `Alphabet`, 'a'..='z' in a fixed array per node by default.
+/// Use `Trie<Unicode>` for any `str` and `Trie<Bytes>` for byte strings.
+/// A `TrieMap` without values underneath.
+pub struct Trie<A: Alphabet = Lowercase> {
+    words: TrieMap<(), A>,
+}
+
+impl<A: Alphabet> Default for Trie<A> {
+    fn default() -> Self {
+        Self {
+            words: TrieMap::new(),
+        }
+    }
+}
+
+impl<A: Alphabet> Trie<A> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Fails without changing the trie if the word has a character the alphabet can't store
+    pub fn insert(&mut self, word: &A::Key) -> Result<(), TrieError> {
+        self.words.insert(word, ()).map(|_| ())
+    }
+
+    /// Returns whether the word was there
+    pub fn remove(&mut self, word: &A::Key) -> bool {
+        self.words.remove(word).is_some()
+    }
+
+    /// A word the alphabet can't store is never in the trie
+    pub fn search(&self, word: &A::Key) -> bool {
+        self.words.contains_key(word)
+    }
+
+    pub fn starts_with(&self, prefix: &A::Key) -> bool {
+        self.words.node(prefix).is_some()
+    }
+
+    /// Number of words starting with `prefix`, O(|prefix|)
+    pub fn count_with_prefix(&self, prefix: &A::Key) -> usize {
+        self.words.count_with_prefix(prefix)
+    }
+
+    /// All words in lexicographic order
+    pub fn words(&self) -> impl Iterator<Item = A::OwnedKey> + '_ {
+        self.words.keys()
+    }
+
+    /// The words starting with `prefix` in lexicographic order
+    pub fn words_with_prefix(&self, prefix: &A::Key) -> impl Iterator<Item = A::OwnedKey> + '_ {
+        self.words.iter_prefix(prefix).map(|(word, _)| word)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Edit distance search, see `TrieMap::fuzzy_search`
+    pub fn fuzzy_search(&self, word: &A::Key, max_edits: usize) -> Vec<(A::OwnedKey, usize)> {
+        self.words
+            .fuzzy_search(word, max_edits)
+            .into_iter()
+            .map(|(word, _, distance)| (word, distance))
+            .collect()
+    }
+
+    /// Bytes used by the trie, see `RadixTrie::memory_usage` for the compressed variant
+    pub fn memory_usage(&self) -> usize {
+        self.words.memory_usage()
+    }
+}
+
+impl<A: Alphabet<Key = str>> Trie<A> {
+    /// Words matching `pattern`, `.` for any character and `*` for any run,
+    /// see `TrieMap::search_pattern`
+    pub fn search_pattern(&self, pattern: &str) -> Vec<A::OwnedKey> {
+        self.words
+            .search_pattern(pattern)
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect()
+    }
+}
+
+/*
+ * Your Trie object will be instantiated and called as such:
+ * let obj = Trie::new();
+ * obj.insert(word);
+ * let ret_2: bool = obj.search(word);
+ * let ret_3: bool = obj.starts_with(prefix);
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alphabet::{Bytes, Unicode};
+
+    #[test]
+    fn initialize() {
+        let trie: Trie = Trie::new();
+        assert!(!trie.search("fizz"));
+        assert!(!trie.starts_with("fizz"));
+    }
+
+    #[test]
+    fn simple_trie() {
+        let mut trie: Trie = Trie::new();
+
+        trie.insert("buzz").unwrap();
+        trie.insert("fizzbuzz").unwrap();
+
+        assert!(trie.search("fizzbuzz"));
+        assert!(trie.search("buzz"));
+        assert!(trie.starts_with("fizz"));
+        assert!(!trie.search("fizz"));
+    }
+
+    #[test]
+    fn lowercase_rejects_other_characters() {
+        let mut trie: Trie = Trie::new();
+
+        assert_eq!(
+            trie.insert("fizZ"),
+            Err(TrieError::UnsupportedCharacter {
+                character: 'Z',
+                position: 3
+            })
+        );
+        // Nothing of the rejected word was stored
+        assert!(!trie.starts_with("f"));
+        assert!(!trie.search("Fizz"));
+        assert!(!trie.starts_with("été"));
+    }
+
+    #[test]
+    fn unicode_keys() {
+        let mut trie: Trie<Unicode> = Trie::new();
+        for word in ["Straße", "straße", "日本語", "日本", "R2-D2"] {
+            trie.insert(word).unwrap();
+        }
+
+        assert!(trie.search("Straße"));
+        assert!(trie.search("日本"));
+        assert!(trie.starts_with("日本語"));
+        assert!(trie.starts_with("R2"));
+        assert!(!trie.search("Strasse"));
+        assert!(!trie.search("日"));
+    }
+
+    #[test]
+    fn byte_keys() {
+        let mut trie: Trie<Bytes> = Trie::new();
+        trie.insert(&[0xff, 0x00, 0x10]).unwrap();
+        trie.insert(b"plain").unwrap();
+
+        assert!(trie.search(&[0xff, 0x00, 0x10]));
+        assert!(trie.starts_with(&[0xff]));
+        assert!(trie.search(b"plain"));
+        assert!(!trie.search(&[0xff, 0x00]));
+    }
+
+    #[test]
+    fn remove_and_list() {
+        let mut trie: Trie = Trie::new();
+        for word in ["fizz", "fizzbuzz", "buzz", "fig"] {
+            trie.insert(word).unwrap();
+        }
+
+        assert_eq!(trie.count_with_prefix("fi"), 3);
+        assert!(trie.remove("fizz"));
+        assert!(!trie.remove("fizz"));
+        assert!(trie.starts_with("fizz"));
+        assert_eq!(
+            trie.words().collect::<Vec<_>>(),
+            vec!["buzz", "fig", "fizzbuzz"]
+        );
+        assert_eq!(
+            trie.words_with_prefix("fi").collect::<Vec<_>>(),
+            vec!["fig", "fizzbuzz"]
+        );
+
+        trie.remove("fizzbuzz");
+        assert!(!trie.starts_with("fiz"));
+        assert_eq!(trie.len(), 2);
+    }
+
+    #[test]
+    fn typo_tolerant_lookups() {
+        let mut trie: Trie = Trie::new();
+        for word in ["fizz", "fizzbuzz", "buzz", "fig", "fuzz"] {
+            trie.insert(word).unwrap();
+        }
+
+        assert_eq!(trie.search_pattern("f.zz"), vec!["fizz", "fuzz"]);
+        assert_eq!(trie.search_pattern("*buzz"), vec!["buzz", "fizzbuzz"]);
+        assert_eq!(trie.fuzzy_search("fuz", 1), vec![("fuzz".to_string(), 1)]);
+        assert_eq!(trie.fuzzy_search("fizy", 1), vec![("fizz".to_string(), 1)]);
+    }
+}
@@ -0,0 +1,357 @@
+//! Compressed (radix / Patricia) trie over byte strings.
+//!
+//! A plain trie spends a whole node per character, even along long chains where every node
+//! has a single child. Here an edge carries a whole byte string instead and chains collapse
+//! into one edge, so there are at most twice as many nodes as keys.
+//! - `insert` splits an edge where the new key leaves its label
+//! - `remove` merges a node that is left with a single child and no key into its parent edge,
+//!   and drops edges that lead to no key anymore
+//!
+//! Children are kept sorted by the first byte of their label, which is unique among siblings.
+
+use std::mem;
+
+struct Edge {
+    label: Box<[u8]>,
+    node: Node,
+}
+
+#[derive(Default)]
+struct Node {
+    children: Vec<Edge>,
+    is_end: bool,
+}
+
+impl Node {
+    fn child(&self, first: u8) -> Result<usize, usize> {
+        self.children
+            .binary_search_by(|edge| edge.label[0].cmp(&first))
+    }
+}
+
+#[derive(Default)]
+pub struct RadixTrie {
+    root: Node,
+    len: usize,
+}
+
+impl RadixTrie {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the word was new
+    pub fn insert<K: AsRef<[u8]> + ?Sized>(&mut self, word: &K) -> bool {
+        let mut rest = word.as_ref();
+        let mut node = &mut self.root;
+
+        loop {
+            let Some(&first) = rest.first() else {
+                let is_new = !node.is_end;
+                node.is_end = true;
+                self.len += is_new as usize;
+                return is_new;
+            };
+
+            let index = match node.child(first) {
+                Ok(index) => index,
+                Err(index) => {
+                    node.children.insert(
+                        index,
+                        Edge {
+                            label: rest.into(),
+                            node: Node {
+                                children: Vec::new(),
+                                is_end: true,
+                            },
+                        },
+                    );
+                    self.len += 1;
+                    return true;
+                }
+            };
+
+            let edge = &mut node.children[index];
+            let common = common_prefix(&edge.label, rest);
+            if common < edge.label.len() {
+                // The word leaves the label in the middle: split the edge there
+                let tail = Edge {
+                    label: edge.label[common..].into(),
+                    node: mem::take(&mut edge.node),
+                };
+                edge.label = edge.label[..common].into();
+                edge.node.children.push(tail);
+            }
+
+            rest = &rest[common..];
+            node = &mut edge.node;
+        }
+    }
+
+    /// Returns whether the word was there
+    pub fn remove<K: AsRef<[u8]> + ?Sized>(&mut self, word: &K) -> bool {
+        let removed = Self::remove_from(&mut self.root, word.as_ref());
+        self.len -= removed as usize;
+        removed
+    }
+
+    fn remove_from(node: &mut Node, rest: &[u8]) -> bool {
+        let Some(&first) = rest.first() else {
+            return mem::replace(&mut node.is_end, false);
+        };
+        let Ok(index) = node.child(first) else {
+            return false;
+        };
+
+        let edge = &mut node.children[index];
+        if !rest.starts_with(&edge.label) {
+            return false;
+        }
+        if !Self::remove_from(&mut edge.node, &rest[edge.label.len()..]) {
+            return false;
+        }
+
+        // Clean up what the removal left behind below this edge
+        if edge.node.is_end {
+            return true;
+        }
+        match edge.node.children.len() {
+            0 => {
+                node.children.remove(index);
+            }
+            1 => {
+                let child = edge.node.children.pop().unwrap();
+                edge.label = [&edge.label[..], &child.label[..]].concat().into();
+                edge.node = child.node;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Traverses the trie along `word`. Returns the node it ends at and how much of the
+    /// label leading there is left over if `word` ends in the middle of an edge.
+    fn end_node(&self, word: &[u8]) -> Option<(&Node, usize)> {
+        let mut rest = word;
+        let mut node = &self.root;
+
+        while let Some(&first) = rest.first() {
+            let edge = &node.children[node.child(first).ok()?];
+            if edge.label.starts_with(rest) {
+                return Some((&edge.node, edge.label.len() - rest.len()));
+            }
+            if !rest.starts_with(&edge.label) {
+                return None;
+            }
+            rest = &rest[edge.label.len()..];
+            node = &edge.node;
+        }
+
+        Some((node, 0))
+    }
+
+    pub fn search<K: AsRef<[u8]> + ?Sized>(&self, word: &K) -> bool {
+        matches!(self.end_node(word.as_ref()), Some((node, 0)) if node.is_end)
+    }
+
+    pub fn starts_with<K: AsRef<[u8]> + ?Sized>(&self, prefix: &K) -> bool {
+        self.end_node(prefix.as_ref()).is_some()
+    }
+
+    /// Number of nodes, the root included
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children.iter().map(|edge| &edge.node));
+        }
+        count
+    }
+
+    /// Bytes used by the trie: the struct itself, every child vector and every label
+    pub fn memory_usage(&self) -> usize {
+        let mut bytes = mem::size_of::<Self>();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            bytes += node.children.capacity() * mem::size_of::<Edge>();
+            for edge in &node.children {
+                bytes += edge.label.len();
+                stack.push(&edge.node);
+            }
+        }
+        bytes
+    }
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::Trie;
+
+    #[test]
+    fn initialize() {
+        let trie = RadixTrie::new();
+        assert!(!trie.search("fizz"));
+        assert!(!trie.starts_with("fizz"));
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    fn split_on_insert() {
+        let mut trie = RadixTrie::new();
+        assert!(trie.insert("romane"));
+        assert!(trie.insert("romanus"));
+        assert!(trie.insert("romulus"));
+        assert!(trie.insert("rubens"));
+        assert!(trie.insert("rom"));
+        assert!(!trie.insert("romane"));
+
+        // root - "r" - "om" - "an" - "e"
+        //            |      |      \ "us"
+        //            |      \ "ulus"
+        //            \ "ubens"
+        assert_eq!(trie.node_count(), 8);
+        assert_eq!(trie.len(), 5);
+        for word in ["romane", "romanus", "romulus", "rubens", "rom"] {
+            assert!(trie.search(word));
+        }
+        assert!(!trie.search("roman"));
+        assert!(!trie.search("r"));
+        assert!(trie.starts_with("roma"));
+        assert!(trie.starts_with("romanu"));
+        assert!(!trie.starts_with("romb"));
+        assert!(!trie.starts_with("romanusx"));
+    }
+
+    #[test]
+    fn merge_on_remove() {
+        let mut trie = RadixTrie::new();
+        for word in ["test", "team", "toast", "te"] {
+            trie.insert(word);
+        }
+        let nodes = trie.node_count();
+
+        assert!(trie.remove("team"));
+        assert!(!trie.remove("team"));
+        assert!(!trie.remove("tea"));
+        // "te" keeps its node, but nothing else hangs off the "a" branch anymore
+        assert_eq!(trie.node_count(), nodes - 1);
+
+        assert!(trie.remove("te"));
+        // "t" - "e" - "st" merged back into "t" - "est"
+        assert_eq!(trie.node_count(), nodes - 2);
+        assert!(trie.search("test"));
+        assert!(trie.search("toast"));
+        assert!(!trie.starts_with("tea"));
+
+        trie.remove("test");
+        trie.remove("toast");
+        assert!(trie.is_empty());
+        assert_eq!(trie.node_count(), 1);
+    }
+
+    #[test]
+    fn byte_keys_and_empty_word() {
+        let mut trie = RadixTrie::new();
+        trie.insert(&[0xff, 0x00, 0x01][..]);
+        trie.insert("");
+
+        assert!(trie.search(""));
+        assert!(trie.search(&[0xff, 0x00, 0x01][..]));
+        assert!(trie.starts_with(&[0xff][..]));
+        assert!(!trie.search(&[0xff][..]));
+    }
+
+    #[test]
+    fn matches_trie() {
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+        let mut radix = RadixTrie::new();
+        let mut plain: Trie = Trie::new();
+        let words: Vec<String> = (0..2000)
+            .map(|_| {
+                let length = rng.gen_range(0..8);
+                (0..length)
+                    .map(|_| rng.gen_range(b'a'..b'd') as char)
+                    .collect()
+            })
+            .collect();
+
+        for (index, word) in words.iter().enumerate() {
+            if index % 3 == 2 {
+                assert_eq!(radix.remove(word.as_str()), plain.remove(word));
+            } else {
+                radix.insert(word.as_str());
+                plain.insert(word).unwrap();
+            }
+        }
+
+        assert_eq!(radix.len(), plain.len());
+        for word in &words {
+            assert_eq!(radix.search(word.as_str()), plain.search(word));
+            let prefix = &word[..word.len() / 2];
+            assert_eq!(radix.starts_with(prefix), plain.starts_with(prefix));
+        }
+    }
+
+    #[test]
+    fn uses_less_memory_than_trie() {
+        use rand::{thread_rng, Rng};
+
+        // Long random words: after the first few characters every word is a single chain
+        let mut rng = thread_rng();
+        let mut radix = RadixTrie::new();
+        let mut plain: Trie = Trie::new();
+        for _ in 0..1000 {
+            let word: String = (0..20)
+                .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                .collect();
+            radix.insert(word.as_str());
+            plain.insert(&word).unwrap();
+        }
+
+        assert!(radix.memory_usage() * 10 < plain.memory_usage());
+    }
+
+    #[test]
+    #[ignore]
+    fn memory_report() {
+        use crate::trie::alphabet::Bytes;
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+        let mut radix = RadixTrie::new();
+        let mut lowercase: Trie = Trie::new();
+        let mut bytes: Trie<Bytes> = Trie::new();
+        for _ in 0..100_000 {
+            let length = rng.gen_range(3..12);
+            let word: String = (0..length)
+                .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                .collect();
+            radix.insert(word.as_str());
+            lowercase.insert(&word).unwrap();
+            bytes.insert(word.as_bytes()).unwrap();
+        }
+
+        println!(
+            "100k words: RadixTrie {} KiB, Trie {} KiB, Trie<Bytes> {} KiB",
+            radix.memory_usage() / 1024,
+            lowercase.memory_usage() / 1024,
+            bytes.memory_usage() / 1024
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Approximate lookups on a `TrieMap`: wildcard patterns and edit distance.
+//!
+//! Both are a depth first walk that carries a small state per node and drops a branch as
+//! soon as that state says nothing below can match, so shared prefixes are matched once.
+//! - `search_pattern` simulates the pattern as an NFA: the state is the set of pattern
+//!   positions still alive, `*` keeps its position alive on every symbol
+//! - `fuzzy_search` carries the Levenshtein row of the path against the word: entry `j` is
+//!   the distance to the first `j` symbols of the word. A branch is dropped once the
+//!   smallest entry is over the budget, since the row never decreases going down.
+//!
+//! A character the alphabet can't store is not an error here, it simply matches nothing.
+
+use super::alphabet::{Alphabet, Links};
+use super::trie_map::{Node, Summary, TrieMap};
+
+#[derive(Clone, Copy)]
+enum Token<S> {
+    // `None` for a character the alphabet can't store
+    Symbol(Option<S>),
+    // '.'
+    Any,
+    // '*'
+    AnyRun,
+}
+
+// A node still to visit: its depth, the symbol leading to it, the node and its search state
+type Pending<'a, V, A, S, T> = (usize, Option<<A as Alphabet>::Symbol>, &'a Node<V, A, S>, T);
+
+impl<V, A: Alphabet<Key = str>, S: Summary<V>> TrieMap<V, A, S> {
+    /// The entries whose key matches `pattern` in lexicographic key order.
+    /// `.` matches any single character and `*` any run of characters, the empty one too.
+    pub fn search_pattern(&self, pattern: &str) -> Vec<(A::OwnedKey, &V)> {
+        let tokens: Vec<Token<A::Symbol>> = pattern
+            .chars()
+            .map(|character| match character {
+                '.' => Token::Any,
+                '*' => Token::AnyRun,
+                _ => {
+                    let mut buffer = [0; 4];
+                    let symbol = A::symbols(character.encode_utf8(&mut buffer)).next();
+                    Token::Symbol(symbol.and_then(Result::ok))
+                }
+            })
+            .collect();
+
+        let mut start = vec![false; tokens.len() + 1];
+        start[0] = true;
+        close(&tokens, &mut start);
+
+        let mut matches = Vec::new();
+        let mut path = Vec::new();
+        let mut stack: Vec<Pending<V, A, S, Vec<bool>>> = vec![(0, None, self.root(), start)];
+        while let Some((depth, symbol, node, alive)) = stack.pop() {
+            path.truncate(depth);
+            path.extend(symbol);
+
+            if let (true, Some(value)) = (alive[tokens.len()], &node.value) {
+                matches.push((A::to_key(&path), value));
+            }
+
+            // Reversed, so the smallest symbol is popped first
+            let children: Vec<_> = node.links.iter().collect();
+            for (symbol, child) in children.into_iter().rev() {
+                let next = step(&tokens, &alive, symbol);
+                if next.contains(&true) {
+                    stack.push((path.len(), Some(symbol), child, next));
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+impl<V, A: Alphabet, S: Summary<V>> TrieMap<V, A, S> {
+    /// The entries whose key is at most `max_edits` insertions, deletions or substitutions
+    /// away from `word`, with that distance. Closest first, then in lexicographic key order.
+    pub fn fuzzy_search(&self, word: &A::Key, max_edits: usize) -> Vec<(A::OwnedKey, &V, usize)> {
+        let word: Vec<Option<A::Symbol>> = A::symbols(word).map(Result::ok).collect();
+
+        let mut matches = Vec::new();
+        let mut path = Vec::new();
+        let first_row: Vec<usize> = (0..=word.len()).collect();
+        let mut stack: Vec<Pending<V, A, S, Vec<usize>>> = vec![(0, None, self.root(), first_row)];
+        while let Some((depth, symbol, node, row)) = stack.pop() {
+            path.truncate(depth);
+            path.extend(symbol);
+
+            let distance = row[word.len()];
+            if let (true, Some(value)) = (distance <= max_edits, &node.value) {
+                matches.push((A::to_key(&path), value, distance));
+            }
+
+            let children: Vec<_> = node.links.iter().collect();
+            for (symbol, child) in children.into_iter().rev() {
+                let mut next = Vec::with_capacity(row.len());
+                next.push(row[0] + 1);
+                for (j, &expected) in word.iter().enumerate() {
+                    let substitute = row[j] + (expected != Some(symbol)) as usize;
+                    let insert = next[j] + 1;
+                    let delete = row[j + 1] + 1;
+                    next.push(substitute.min(insert).min(delete));
+                }
+                if next.iter().min().is_some_and(|&best| best <= max_edits) {
+                    stack.push((path.len(), Some(symbol), child, next));
+                }
+            }
+        }
+
+        // Stable, so equal distances stay in lexicographic order
+        matches.sort_by_key(|&(_, _, distance)| distance);
+        matches
+    }
+}
+
+// Pattern positions reached from `alive` by reading `symbol`
+fn step<S: PartialEq>(tokens: &[Token<S>], alive: &[bool], symbol: S) -> Vec<bool> {
+    let mut next = vec![false; alive.len()];
+    for (position, token) in tokens.iter().enumerate() {
+        if !alive[position] {
+            continue;
+        }
+        match token {
+            Token::Symbol(expected) if expected.as_ref() == Some(&symbol) => {
+                next[position + 1] = true
+            }
+            Token::Symbol(_) => {}
+            Token::Any => next[position + 1] = true,
+            Token::AnyRun => next[position] = true,
+        }
+    }
+    close(tokens, &mut next);
+    next
+}
+
+// A live `*` may also match nothing, so the position after it is alive too
+fn close<S>(tokens: &[Token<S>], alive: &mut [bool]) {
+    for (position, token) in tokens.iter().enumerate() {
+        if alive[position] && matches!(token, Token::AnyRun) {
+            alive[position + 1] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::alphabet::Unicode;
+
+    fn dictionary() -> TrieMap<usize> {
+        let mut map = TrieMap::new();
+        for (index, key) in [
+            "cat", "cart", "care", "cars", "dog", "dot", "do", "", "scar",
+        ]
+        .iter()
+        .enumerate()
+        {
+            map.insert(*key, index).unwrap();
+        }
+        map
+    }
+
+    fn keys<V>(matches: Vec<(String, &V)>) -> Vec<String> {
+        matches.into_iter().map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn wildcard_patterns() {
+        let map = dictionary();
+
+        assert_eq!(keys(map.search_pattern("ca.")), vec!["cat"]);
+        assert_eq!(keys(map.search_pattern("car.")), vec!["care", "cars", "cart"]);
+        assert_eq!(
+            keys(map.search_pattern("ca*")),
+            vec!["care", "cars", "cart", "cat"]
+        );
+        assert_eq!(
+            keys(map.search_pattern("*ar*")),
+            vec!["care", "cars", "cart", "scar"]
+        );
+        assert_eq!(keys(map.search_pattern("do*")), vec!["do", "dog", "dot"]);
+        assert_eq!(keys(map.search_pattern("d.")), vec!["do"]);
+        assert_eq!(map.search_pattern("*").len(), 9);
+        assert_eq!(keys(map.search_pattern("")), vec![""]);
+        assert!(map.search_pattern("c.").is_empty());
+        // A character Lowercase can't store matches nothing instead of failing
+        assert!(map.search_pattern("C*").is_empty());
+    }
+
+    #[test]
+    fn repeated_stars_stay_linear() {
+        let mut map: TrieMap<()> = TrieMap::new();
+        let long = "a".repeat(60);
+        map.insert(&long, ()).unwrap();
+
+        // Backtracking over the stars would take forever on the missing 'b'
+        assert_eq!(map.search_pattern(&"a*".repeat(30)).len(), 1);
+        assert!(map
+            .search_pattern(&format!("{}b", "*a".repeat(30)))
+            .is_empty());
+    }
+
+    #[test]
+    fn fuzzy_by_distance() {
+        let map = dictionary();
+
+        let found: Vec<(String, usize)> = map
+            .fuzzy_search("cat", 1)
+            .into_iter()
+            .map(|(key, _, distance)| (key, distance))
+            .collect();
+        assert_eq!(found, vec![("cat".to_string(), 0), ("cart".to_string(), 1)]);
+
+        let distances: Vec<(String, usize)> = map
+            .fuzzy_search("dig", 2)
+            .into_iter()
+            .map(|(key, _, distance)| (key, distance))
+            .collect();
+        assert_eq!(
+            distances,
+            vec![
+                ("dog".to_string(), 1),
+                ("do".to_string(), 2),
+                ("dot".to_string(), 2)
+            ]
+        );
+
+        assert_eq!(map.fuzzy_search("", 0)[0].0, "");
+        // Unsupported characters cost one substitution
+        assert_eq!(map.fuzzy_search("dOg", 1)[0].0, "dog");
+    }
+
+    #[test]
+    fn fuzzy_matches_brute_force() {
+        use rand::{thread_rng, Rng};
+
+        fn levenshtein(a: &[char], b: &[char]) -> usize {
+            let mut row: Vec<usize> = (0..=b.len()).collect();
+            for (i, x) in a.iter().enumerate() {
+                let mut next = vec![i + 1];
+                for (j, y) in b.iter().enumerate() {
+                    next.push(
+                        (row[j] + (x != y) as usize)
+                            .min(next[j] + 1)
+                            .min(row[j + 1] + 1),
+                    );
+                }
+                row = next;
+            }
+            row[b.len()]
+        }
+
+        let mut rng = thread_rng();
+        let mut map: TrieMap<(), Unicode> = TrieMap::new();
+        let random_word = |rng: &mut rand::rngs::ThreadRng| -> String {
+            let length = rng.gen_range(0..7);
+            (0..length).map(|_| rng.gen_range('a'..'e')).collect()
+        };
+        let words: Vec<String> = (0..300).map(|_| random_word(&mut rng)).collect();
+        for word in &words {
+            map.insert(word, ()).unwrap();
+        }
+
+        for _ in 0..20 {
+            let query = random_word(&mut rng);
+            let query_chars: Vec<char> = query.chars().collect();
+            let mut expected: Vec<(String, usize)> = map
+                .keys()
+                .map(|key| {
+                    let distance = levenshtein(&key.chars().collect::<Vec<_>>(), &query_chars);
+                    (key, distance)
+                })
+                .filter(|&(_, distance)| distance <= 2)
+                .collect();
+            expected.sort_by_key(|&(_, distance)| distance);
+
+            let found: Vec<(String, usize)> = map
+                .fuzzy_search(&query, 2)
+                .into_iter()
+                .map(|(key, _, distance)| (key, distance))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Map from keys to values stored as a trie.
+//!
+//! Every node counts the keys in its subtree, so `count_with_prefix` only walks the prefix.
+//! The counts also drive pruning: when `remove` brings a child's count to zero,
+//! that whole branch holds no key anymore and is dropped at once.
+//! Iteration is a preorder walk, children in symbol order, which is lexicographic key order.
+//!
+//! A node can also cache a `Summary` of the values below it, e.g. the highest weight for
+//! `Autocomplete`. `insert` and `remove` recompute it on the path of the key,
+//! O(|key| * fanout). `()` caches nothing and costs nothing.
+
+use super::alphabet::{Alphabet, Links, Lowercase, TrieError};
+
+/// What a node caches about the values in its subtree
+pub trait Summary<V>: Copy + Default {
+    /// Nothing to keep up to date, lets `TrieMap` skip the walk back up
+    const IS_EMPTY: bool = false;
+
+    /// The summary of a node from its own value and the summaries of its children
+    fn summarize(value: Option<&V>, children: impl Iterator<Item = Self>) -> Self;
+}
+
+impl<V> Summary<V> for () {
+    const IS_EMPTY: bool = true;
+
+    fn summarize(_: Option<&V>, _: impl Iterator<Item = Self>) -> Self {}
+}
+
+pub(crate) struct Node<V, A: Alphabet, S = ()> {
+    pub(crate) links: A::Links<Node<V, A, S>>,
+    pub(crate) value: Option<V>,
+    // Keys stored in this subtree, including this node
+    pub(crate) count: usize,
+    pub(crate) summary: S,
+}
+
+impl<V, A: Alphabet, S: Default> Default for Node<V, A, S> {
+    fn default() -> Self {
+        Self {
+            links: Default::default(),
+            value: None,
+            count: 0,
+            summary: S::default(),
+        }
+    }
+}
+
+pub struct TrieMap<V, A: Alphabet = Lowercase, S: Summary<V> = ()> {
+    root: Node<V, A, S>,
+}
+
+impl<V, A: Alphabet, S: Summary<V>> Default for TrieMap<V, A, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, A: Alphabet> TrieMap<V, A> {
+    /// Only without a summary, which a changed value would leave stale
+    pub fn get_mut(&mut self, key: &A::Key) -> Option<&mut V> {
+        self.node_mut(key)?.value.as_mut()
+    }
+}
+
+impl<V, A: Alphabet, S: Summary<V>> TrieMap<V, A, S> {
+    pub fn new() -> Self {
+        Self {
+            root: Node::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.count == 0
+    }
+
+    /// Returns the old value if the key was already there.
+    /// Fails without changing the map if the key has a character the alphabet can't store.
+    pub fn insert(&mut self, key: &A::Key, value: V) -> Result<Option<V>, TrieError> {
+        // Check everything first, so a bad key doesn't leave a dangling branch behind
+        A::symbols(key).try_for_each(|symbol| symbol.map(|_| ()))?;
+
+        let mut node = &mut self.root;
+        for symbol in A::symbols(key).flatten() {
+            node = node.links.get_or_insert_with(symbol, Node::default);
+        }
+        let old = node.value.replace(value);
+
+        if old.is_none() {
+            let mut node = &mut self.root;
+            node.count += 1;
+            for symbol in A::symbols(key).flatten() {
+                node = node.links.get_mut(symbol).unwrap();
+                node.count += 1;
+            }
+        }
+        self.resummarize(key);
+        Ok(old)
+    }
+
+    pub fn get(&self, key: &A::Key) -> Option<&V> {
+        self.node(key)?.value.as_ref()
+    }
+
+    pub fn contains_key(&self, key: &A::Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Takes the key out and drops every node that no longer leads to a key
+    pub fn remove(&mut self, key: &A::Key) -> Option<V> {
+        let value = self.node_mut(key)?.value.take()?;
+
+        let mut node = &mut self.root;
+        node.count -= 1;
+        for symbol in A::symbols(key).flatten() {
+            let child = node.links.get_mut(symbol).unwrap();
+            if child.count == 1 {
+                node.links.remove(symbol);
+                break;
+            }
+            child.count -= 1;
+            node = node.links.get_mut(symbol).unwrap();
+        }
+        self.resummarize(key);
+        Some(value)
+    }
+
+    /// Number of keys starting with `prefix`, O(|prefix|)
+    pub fn count_with_prefix(&self, prefix: &A::Key) -> usize {
+        self.node(prefix).map_or(0, |node| node.count)
+    }
+
+    /// All entries in lexicographic key order
+    pub fn iter(&self) -> Iter<'_, V, A, S> {
+        Iter::new(&self.root, Vec::new())
+    }
+
+    /// The entries whose key starts with `prefix`, in lexicographic key order
+    pub fn iter_prefix(&self, prefix: &A::Key) -> Iter<'_, V, A, S> {
+        let symbols: Result<Vec<A::Symbol>, TrieError> = A::symbols(prefix).collect();
+        match (self.node(prefix), symbols) {
+            (Some(node), Ok(symbols)) => Iter::new(node, symbols),
+            _ => Iter::empty(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = A::OwnedKey> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Bytes used by the map: the struct itself, every boxed node and the link storage
+    pub fn memory_usage(&self) -> usize {
+        let mut bytes = std::mem::size_of::<Self>();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            bytes += node.links.allocated_bytes();
+            for (_, child) in node.links.iter() {
+                bytes += std::mem::size_of::<Node<V, A, S>>();
+                stack.push(child);
+            }
+        }
+        bytes
+    }
+
+    pub(crate) fn root(&self) -> &Node<V, A, S> {
+        &self.root
+    }
+
+    // Recomputes the summaries on the path of `key`, as far as it still exists
+    fn resummarize(&mut self, key: &A::Key) {
+        if S::IS_EMPTY {
+            return;
+        }
+        let symbols: Vec<A::Symbol> = A::symbols(key).flatten().collect();
+        Self::resummarize_path(&mut self.root, &symbols);
+    }
+
+    fn resummarize_path(node: &mut Node<V, A, S>, symbols: &[A::Symbol]) {
+        if let Some((&symbol, rest)) = symbols.split_first() {
+            if let Some(child) = node.links.get_mut(symbol) {
+                Self::resummarize_path(child, rest);
+            }
+        }
+        let children = node.links.iter().map(|(_, child)| child.summary);
+        node.summary = S::summarize(node.value.as_ref(), children);
+    }
+
+    /// The node at the end of `key`, `None` if no stored key starts with it
+    pub(crate) fn node(&self, key: &A::Key) -> Option<&Node<V, A, S>> {
+        let mut node = &self.root;
+        for symbol in A::symbols(key) {
+            node = node.links.get(symbol.ok()?)?;
+        }
+        Some(node)
+    }
+
+    fn node_mut(&mut self, key: &A::Key) -> Option<&mut Node<V, A, S>> {
+        let mut node = &mut self.root;
+        for symbol in A::symbols(key) {
+            node = node.links.get_mut(symbol.ok()?)?;
+        }
+        Some(node)
+    }
+}
+
+// A node still to visit: its depth, the symbol leading to it and the node
+type Pending<'a, V, A, S> = (usize, Option<<A as Alphabet>::Symbol>, &'a Node<V, A, S>);
+
+/// Lexicographic iterator over `(key, &value)`, see `TrieMap::iter`
+pub struct Iter<'a, V, A: Alphabet, S = ()> {
+    // Popped in preorder
+    stack: Vec<Pending<'a, V, A, S>>,
+    // Symbols from the root to the node popped last
+    path: Vec<A::Symbol>,
+    remaining: usize,
+}
+
+impl<'a, V, A: Alphabet, S> Iter<'a, V, A, S> {
+    fn new(start: &'a Node<V, A, S>, path: Vec<A::Symbol>) -> Self {
+        Self {
+            stack: vec![(path.len(), None, start)],
+            path,
+            remaining: start.count,
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            stack: Vec::new(),
+            path: Vec::new(),
+            remaining: 0,
+        }
+    }
+}
+
+impl<'a, V, A: Alphabet, S> Iterator for Iter<'a, V, A, S> {
+    type Item = (A::OwnedKey, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, symbol, node)) = self.stack.pop() {
+            self.path.truncate(depth);
+            self.path.extend(symbol);
+
+            // Reversed, so the smallest symbol is popped first
+            let children: Vec<_> = node.links.iter().collect();
+            for (symbol, child) in children.into_iter().rev() {
+                self.stack.push((self.path.len(), Some(symbol), child));
+            }
+
+            if let Some(value) = &node.value {
+                self.remaining -= 1;
+                return Some((A::to_key(&self.path), value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V, A: Alphabet, S> ExactSizeIterator for Iter<'_, V, A, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::alphabet::Unicode;
+
+    fn fruit() -> TrieMap<u32> {
+        let mut map = TrieMap::new();
+        for (key, value) in [
+            ("banana", 3),
+            ("apple", 1),
+            ("app", 7),
+            ("apricot", 2),
+            ("band", 5),
+            ("cherry", 4),
+        ] {
+            map.insert(key, value).unwrap();
+        }
+        map
+    }
+
+    #[test]
+    fn insert_get_and_overwrite() {
+        let mut map = fruit();
+
+        assert_eq!(map.len(), 6);
+        assert_eq!(map.get("app"), Some(&7));
+        assert_eq!(map.get("ap"), None);
+        assert_eq!(map.get("apples"), None);
+
+        assert_eq!(map.insert("app", 8), Ok(Some(7)));
+        *map.get_mut("band").unwrap() += 10;
+        assert_eq!(map.get("app"), Some(&8));
+        assert_eq!(map.get("band"), Some(&15));
+        assert_eq!(map.len(), 6);
+
+        assert!(map.insert("App", 0).is_err());
+        assert_eq!(map.len(), 6);
+    }
+
+    #[test]
+    fn count_with_prefix() {
+        let map = fruit();
+
+        assert_eq!(map.count_with_prefix(""), 6);
+        assert_eq!(map.count_with_prefix("ap"), 3);
+        assert_eq!(map.count_with_prefix("app"), 2);
+        assert_eq!(map.count_with_prefix("ban"), 2);
+        assert_eq!(map.count_with_prefix("bang"), 0);
+        assert_eq!(map.count_with_prefix("B"), 0);
+    }
+
+    #[test]
+    fn remove_prunes_branches() {
+        let mut map = fruit();
+
+        assert_eq!(map.remove("apricot"), Some(2));
+        assert_eq!(map.remove("apricot"), None);
+        assert_eq!(map.remove("ap"), None);
+        // The whole "ri..." branch is gone, not just the value
+        assert!(map.node("apr").is_none());
+        assert_eq!(map.count_with_prefix("ap"), 2);
+
+        // Removing a key that is a prefix of another keeps the longer one
+        assert_eq!(map.remove("app"), Some(7));
+        assert_eq!(map.get("apple"), Some(&1));
+        assert_eq!(map.count_with_prefix("app"), 1);
+
+        for key in ["apple", "banana", "band", "cherry"] {
+            map.remove(key).unwrap();
+        }
+        assert!(map.is_empty());
+        assert!(map.node("").unwrap().links.is_empty());
+    }
+
+    #[test]
+    fn lexicographic_iteration() {
+        let map = fruit();
+
+        let keys: Vec<String> = map.keys().collect();
+        assert_eq!(
+            keys,
+            vec!["app", "apple", "apricot", "banana", "band", "cherry"]
+        );
+
+        let under_ban: Vec<(String, &u32)> = map.iter_prefix("ban").collect();
+        assert_eq!(
+            under_ban,
+            vec![("banana".to_string(), &3), ("band".to_string(), &5)]
+        );
+        assert_eq!(map.iter_prefix("app").len(), 2);
+        assert_eq!(map.iter_prefix("x").count(), 0);
+        assert_eq!(map.iter_prefix("Ä").count(), 0);
+    }
+
+    #[test]
+    fn summary_follows_changes() {
+        // Longest key length in the subtree, counted from the node
+        #[derive(Clone, Copy, Default)]
+        struct Longest(usize);
+
+        impl Summary<usize> for Longest {
+            fn summarize(_: Option<&usize>, children: impl Iterator<Item = Self>) -> Self {
+                Longest(children.map(|Longest(depth)| depth + 1).max().unwrap_or(0))
+            }
+        }
+
+        let mut map: TrieMap<usize, Lowercase, Longest> = TrieMap::new();
+        for key in ["app", "apple", "apricot"] {
+            map.insert(key, key.len()).unwrap();
+        }
+        assert_eq!(map.node("ap").unwrap().summary.0, 5);
+
+        map.remove("apricot");
+        assert_eq!(map.node("ap").unwrap().summary.0, 3);
+        map.remove("apple");
+        assert_eq!(map.node("").unwrap().summary.0, 3);
+        map.remove("app");
+        assert_eq!(map.node("").unwrap().summary.0, 0);
+    }
+
+    #[test]
+    fn unicode_order() {
+        let mut map: TrieMap<usize, Unicode> = TrieMap::new();
+        for (index, key) in ["zebra", "Zebra", "éclair", "eclair", ""]
+            .iter()
+            .enumerate()
+        {
+            map.insert(key, index).unwrap();
+        }
+
+        let keys: Vec<String> = map.keys().collect();
+        assert_eq!(keys, vec!["", "Zebra", "eclair", "zebra", "éclair"]);
+        assert_eq!(map.get(""), Some(&4));
+    }
+}
@@ -0,0 +1,176 @@
+//! Lock-free Union-Find that can be shared between threads behind a plain `Arc`.
+//!
+//! Parent links are `AtomicUsize`s and every method takes `&self`.
+//! - `find` does path splitting: each vertex on the way up is pointed at its grandparent with
+//!   a CAS. A failed CAS only means another thread already shortened the link, so it is ignored.
+//! - `union` links by index: the root with the smaller index is CASed to point at the other
+//!   root. Links always go towards bigger indices, so no thread can ever create a cycle.
+//!   If the CAS fails the root got linked by someone else in the meantime and we retry.
+//!
+//! Linking by index instead of rank avoids packing a rank next to every parent,
+//! path splitting keeps the trees shallow in practice.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct ConcurrentUnionFind {
+    root: Vec<AtomicUsize>,
+    nf_disjoint_sets: AtomicUsize,
+}
+
+impl ConcurrentUnionFind {
+    pub fn new(nf_vertices: usize) -> Self {
+        Self {
+            root: (0..nf_vertices).map(AtomicUsize::new).collect(),
+            nf_disjoint_sets: AtomicUsize::new(nf_vertices),
+        }
+    }
+
+    /// Returns the current root of the vertex.
+    /// With concurrent unions the root may change right after this returns.
+    pub fn find(&self, vertex: usize) -> usize {
+        let mut vertex = vertex;
+        loop {
+            let parent = self.root[vertex].load(Ordering::Acquire);
+            if parent == vertex {
+                return vertex;
+            }
+
+            let grandparent = self.root[parent].load(Ordering::Acquire);
+            if grandparent != parent {
+                let _ = self.root[vertex].compare_exchange_weak(
+                    parent,
+                    grandparent,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                );
+            }
+            vertex = parent;
+        }
+    }
+
+    /// Returns whether this call merged two sets
+    pub fn union(&self, vertex_1: usize, vertex_2: usize) -> bool {
+        loop {
+            let root_1 = self.find(vertex_1);
+            let root_2 = self.find(vertex_2);
+            if root_1 == root_2 {
+                return false;
+            }
+
+            let (child, parent) = if root_1 < root_2 {
+                (root_1, root_2)
+            } else {
+                (root_2, root_1)
+            };
+
+            // Only succeeds if `child` is still a root
+            if self.root[child]
+                .compare_exchange(child, parent, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                self.nf_disjoint_sets.fetch_sub(1, Ordering::AcqRel);
+                return true;
+            }
+        }
+    }
+
+    pub fn is_connected(&self, vertex_1: usize, vertex_2: usize) -> bool {
+        loop {
+            let root_1 = self.find(vertex_1);
+            let root_2 = self.find(vertex_2);
+            if root_1 == root_2 {
+                return true;
+            }
+
+            // root_1 was still a root after we found root_2, so the sets really were apart
+            if self.root[root_1].load(Ordering::Acquire) == root_1 {
+                return false;
+            }
+        }
+    }
+
+    pub fn get_number_of_disjoint_sets(&self) -> usize {
+        self.nf_disjoint_sets.load(Ordering::Acquire)
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::UnionFind;
+    use super::*;
+    use rand::{thread_rng, Rng};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn single_threaded() {
+        let disjoint_set = ConcurrentUnionFind::new(8);
+        for (from, to) in [(0, 1), (0, 2), (1, 6), (6, 7), (3, 4)] {
+            disjoint_set.union(from, to);
+        }
+
+        assert!(disjoint_set.is_connected(2, 7));
+        assert!(!disjoint_set.is_connected(5, 7));
+        assert!(!disjoint_set.union(1, 7));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 3);
+    }
+
+    #[test]
+    fn stress_against_sequential() {
+        let nf_vertices = 20_000;
+        let nf_threads = 8;
+        let mut rng = thread_rng();
+        let edges: Vec<(usize, usize)> = (0..15_000)
+            .map(|_| (rng.gen_range(0..nf_vertices), rng.gen_range(0..nf_vertices)))
+            .collect();
+
+        let concurrent = Arc::new(ConcurrentUnionFind::new(nf_vertices));
+        let handles: Vec<_> = edges
+            .chunks(edges.len() / nf_threads + 1)
+            .map(|chunk| {
+                let concurrent = Arc::clone(&concurrent);
+                let chunk = chunk.to_vec();
+                thread::spawn(move || {
+                    for (index, &(from, to)) in chunk.iter().enumerate() {
+                        concurrent.union(from, to);
+                        // Mix in reads so finds race with unions
+                        if index % 4 == 0 {
+                            concurrent.is_connected(to, from);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut sequential = UnionFind::new(nf_vertices);
+        for &(from, to) in &edges {
+            sequential.union(from, to);
+        }
+
+        assert_eq!(
+            concurrent.get_number_of_disjoint_sets(),
+            sequential.get_number_of_disjoint_sets()
+        );
+
+        // Both must induce the same partition: roots have to map one to one
+        let mut root_map = HashMap::new();
+        for vertex in 0..nf_vertices {
+            let expected = sequential.find(vertex);
+            let actual = concurrent.find(vertex);
+            assert_eq!(*root_map.entry(expected).or_insert(actual), actual);
+        }
+        assert_eq!(root_map.len(), sequential.get_number_of_disjoint_sets());
+    }
+}
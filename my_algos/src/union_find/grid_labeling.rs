@@ -0,0 +1,425 @@
+//! Connected-component labeling of binary grids (occupancy grids, thresholded images).
+//!
+//! `label_grid` is the classic two-pass algorithm:
+//! 1. Row by row every set cell takes the label of an already visited neighbour
+//!    (left and above, plus the two upper diagonals with 8-connectivity) or a fresh one.
+//!    Neighbours with different labels belong to the same region, so those labels are unioned.
+//! 2. Every provisional label is replaced by the final label of its root.
+//!
+//! `StreamingLabeler` does the first pass on one row at a time and only keeps the labels of
+//! the previous row, relabeled densely after every row, so memory stays O(width).
+//! A region is reported as soon as a row no longer touches it.
+
+use super::UnionFind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Cells touching by an edge are connected
+    #[default]
+    Four,
+    /// Cells touching by an edge or a corner are connected
+    Eight,
+}
+
+/// Inclusive row and column range of a region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_row: usize,
+    pub min_col: usize,
+    pub max_row: usize,
+    pub max_col: usize,
+}
+
+impl BoundingBox {
+    fn cell(row: usize, col: usize) -> Self {
+        Self {
+            min_row: row,
+            min_col: col,
+            max_row: row,
+            max_col: col,
+        }
+    }
+
+    fn merge(&mut self, other: &BoundingBox) {
+        self.min_row = self.min_row.min(other.min_row);
+        self.min_col = self.min_col.min(other.min_col);
+        self.max_row = self.max_row.max(other.max_row);
+        self.max_col = self.max_col.max(other.max_col);
+    }
+
+    pub fn height(&self) -> usize {
+        self.max_row - self.min_row + 1
+    }
+
+    pub fn width(&self) -> usize {
+        self.max_col - self.min_col + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Starts at 1, 0 is the background in the label matrix
+    pub label: usize,
+    /// Number of cells
+    pub area: usize,
+    pub bounding_box: BoundingBox,
+}
+
+impl Region {
+    fn cell(row: usize, col: usize) -> Self {
+        Self {
+            label: 0,
+            area: 1,
+            bounding_box: BoundingBox::cell(row, col),
+        }
+    }
+
+    fn merge(&mut self, other: &Region) {
+        self.area += other.area;
+        self.bounding_box.merge(&other.bounding_box);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Labeling {
+    /// Same shape as the grid, 0 for unset cells and the region label otherwise
+    pub labels: Vec<Vec<usize>>,
+    /// `regions[label - 1]`, labels are handed out in row-major order of the first cell
+    pub regions: Vec<Region>,
+}
+
+/// Labels the connected regions of set cells, all rows must have the same length
+pub fn label_grid<R: AsRef<[bool]>>(grid: &[R], connectivity: Connectivity) -> Labeling {
+    let width = grid.first().map_or(0, |row| row.as_ref().len());
+    let mut sets = UnionFind::new(0);
+    let mut provisional: Vec<Vec<Option<usize>>> = Vec::with_capacity(grid.len());
+
+    // First pass, provisional labels are the vertices of `sets`
+    for row in grid {
+        let row = row.as_ref();
+        assert_eq!(
+            row.len(),
+            width,
+            "all rows of the grid must have the same length"
+        );
+
+        let previous = provisional.last().map(Vec::as_slice);
+        let current = label_row(row, previous, connectivity, &mut sets);
+        provisional.push(current);
+    }
+
+    // Second pass, final labels in order of the first cell of every region
+    let mut final_label = vec![0; sets.len()];
+    let mut regions: Vec<Region> = Vec::new();
+    let labels = provisional
+        .iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(col, cell)| {
+                    let Some(id) = *cell else {
+                        return 0;
+                    };
+
+                    let root = sets.find(id);
+                    if final_label[root] == 0 {
+                        regions.push(Region {
+                            label: regions.len() + 1,
+                            ..Region::cell(row, col)
+                        });
+                        final_label[root] = regions.len();
+                    } else {
+                        regions[final_label[root] - 1].merge(&Region::cell(row, col));
+                    }
+                    final_label[root]
+                })
+                .collect()
+        })
+        .collect();
+
+    Labeling { labels, regions }
+}
+
+// First pass over one row: takes a label from a visited neighbour or a fresh vertex of
+// `sets`, and unions the labels of all visited neighbours
+fn label_row(
+    row: &[bool],
+    previous: Option<&[Option<usize>]>,
+    connectivity: Connectivity,
+    sets: &mut UnionFind,
+) -> Vec<Option<usize>> {
+    let mut current: Vec<Option<usize>> = vec![None; row.len()];
+
+    for col in 0..row.len() {
+        if !row[col] {
+            continue;
+        }
+
+        let above = |offset: isize| {
+            let col = col.checked_add_signed(offset)?;
+            previous?.get(col).copied().flatten()
+        };
+        let left = col.checked_sub(1).and_then(|left| current[left]);
+        let neighbors = match connectivity {
+            Connectivity::Four => [left, above(0), None, None],
+            Connectivity::Eight => [left, above(-1), above(0), above(1)],
+        };
+
+        let mut label = None;
+        for neighbor in neighbors.into_iter().flatten() {
+            match label {
+                None => label = Some(neighbor),
+                Some(label) => sets.union(label, neighbor),
+            }
+        }
+        current[col] = Some(label.unwrap_or_else(|| sets.add_vertex()));
+    }
+
+    current
+}
+
+/// Labels a grid that arrives one row at a time.
+///
+/// Only the labels of the previous row are kept, so there is no label matrix.
+/// `push_row` returns the regions the new row completed, `finish` the rest.
+/// Labels count up in the order regions are completed.
+pub struct StreamingLabeler {
+    connectivity: Connectivity,
+    width: usize,
+    nf_rows: usize,
+    // Dense labels 0..open.len() of the previous row
+    previous: Vec<Option<usize>>,
+    // Area and bounding box so far of every region the previous row touches
+    open: Vec<Region>,
+    nf_completed: usize,
+}
+
+impl StreamingLabeler {
+    pub fn new(width: usize, connectivity: Connectivity) -> Self {
+        Self {
+            connectivity,
+            width,
+            nf_rows: 0,
+            previous: vec![None; width],
+            open: Vec::new(),
+            nf_completed: 0,
+        }
+    }
+
+    /// Number of rows pushed so far
+    pub fn nf_rows(&self) -> usize {
+        self.nf_rows
+    }
+
+    /// Labels the next row and returns the regions it does not continue.
+    ///
+    /// Panics if the row is not `width` cells long.
+    pub fn push_row(&mut self, row: &[bool]) -> Vec<Region> {
+        assert_eq!(
+            row.len(),
+            self.width,
+            "row must be {} cells long",
+            self.width
+        );
+
+        // Vertices 0..open.len() are the regions of the previous row, new ones are added after
+        let mut sets = UnionFind::new(self.open.len());
+        let current = label_row(row, Some(&self.previous), self.connectivity, &mut sets);
+
+        let mut merged: Vec<Option<Region>> = vec![None; sets.len()];
+        for (id, region) in self.open.drain(..).enumerate() {
+            merge_into(&mut merged[sets.find(id)], &region);
+        }
+        for (col, cell) in current.iter().enumerate() {
+            if let Some(id) = *cell {
+                merge_into(&mut merged[sets.find(id)], &Region::cell(self.nf_rows, col));
+            }
+        }
+
+        // Relabel the current row densely, whatever it doesn't reach is complete
+        let mut dense = vec![None; sets.len()];
+        self.previous = current
+            .iter()
+            .map(|cell| {
+                let root = sets.find((*cell)?);
+                Some(*dense[root].get_or_insert_with(|| {
+                    self.open.push(merged[root].take().unwrap());
+                    self.open.len() - 1
+                }))
+            })
+            .collect();
+        self.nf_rows += 1;
+
+        merged
+            .into_iter()
+            .flatten()
+            .map(|region| self.complete(region))
+            .collect()
+    }
+
+    /// Returns the regions still touching the last row
+    pub fn finish(mut self) -> Vec<Region> {
+        let open = std::mem::take(&mut self.open);
+        open.into_iter()
+            .map(|region| self.complete(region))
+            .collect()
+    }
+
+    fn complete(&mut self, region: Region) -> Region {
+        self.nf_completed += 1;
+        Region {
+            label: self.nf_completed,
+            ..region
+        }
+    }
+}
+
+fn merge_into(slot: &mut Option<Region>, region: &Region) {
+    match slot {
+        Some(merged) => merged.merge(region),
+        None => *slot = Some(*region),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(rows: &[&str]) -> Vec<Vec<bool>> {
+        rows.iter()
+            .map(|row| row.chars().map(|cell| cell == '#').collect())
+            .collect()
+    }
+
+    fn sorted_areas(regions: &[Region]) -> Vec<usize> {
+        let mut areas: Vec<usize> = regions.iter().map(|region| region.area).collect();
+        areas.sort_unstable();
+        areas
+    }
+
+    #[test]
+    fn four_connectivity() {
+        let grid = parse(&["##..#", "#..##", "..#..", "##..#"]);
+        let labeling = label_grid(&grid, Connectivity::Four);
+
+        assert_eq!(
+            labeling.labels,
+            vec![
+                vec![1, 1, 0, 0, 2],
+                vec![1, 0, 0, 2, 2],
+                vec![0, 0, 3, 0, 0],
+                vec![4, 4, 0, 0, 5],
+            ]
+        );
+        assert_eq!(sorted_areas(&labeling.regions), vec![1, 1, 2, 3, 3]);
+        assert_eq!(
+            labeling.regions[1].bounding_box,
+            BoundingBox {
+                min_row: 0,
+                min_col: 3,
+                max_row: 1,
+                max_col: 4
+            }
+        );
+    }
+
+    #[test]
+    fn eight_connectivity_joins_diagonals() {
+        let grid = parse(&["##..#", "#..##", "..#..", "##..#"]);
+        let labeling = label_grid(&grid, Connectivity::Eight);
+
+        // The centre cell touches the right region and the bottom left one by a corner
+        assert_eq!(labeling.regions.len(), 3);
+        assert_eq!(labeling.labels[2][2], labeling.labels[0][4]);
+        assert_eq!(labeling.labels[3][0], labeling.labels[0][4]);
+        assert_eq!(sorted_areas(&labeling.regions), vec![1, 3, 6]);
+    }
+
+    #[test]
+    fn u_shape_merges_late() {
+        // Both arms get different provisional labels until the bottom row joins them
+        let grid = parse(&["#.#", "#.#", "###"]);
+        let labeling = label_grid(&grid, Connectivity::Four);
+
+        assert_eq!(labeling.regions.len(), 1);
+        assert_eq!(labeling.labels[0], vec![1, 0, 1]);
+        assert_eq!(labeling.regions[0].area, 7);
+        assert_eq!(labeling.regions[0].bounding_box.width(), 3);
+        assert_eq!(labeling.regions[0].bounding_box.height(), 3);
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid: Vec<Vec<bool>> = Vec::new();
+        assert_eq!(label_grid(&grid, Connectivity::Four).regions, vec![]);
+
+        let blank = parse(&["...", "..."]);
+        let labeling = label_grid(&blank, Connectivity::Eight);
+        assert_eq!(labeling.labels, vec![vec![0; 3]; 2]);
+        assert!(labeling.regions.is_empty());
+    }
+
+    #[test]
+    fn streaming_reports_regions_when_they_end() {
+        let grid = parse(&["#.#.", "#.#.", "###.", "...#"]);
+        let mut labeler = StreamingLabeler::new(4, Connectivity::Four);
+
+        assert!(labeler.push_row(&grid[0]).is_empty());
+        assert!(labeler.push_row(&grid[1]).is_empty());
+        assert!(labeler.push_row(&grid[2]).is_empty());
+
+        let completed = labeler.push_row(&grid[3]);
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].label, 1);
+        assert_eq!(completed[0].area, 7);
+
+        let rest = labeler.finish();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].label, 2);
+        assert_eq!(rest[0].bounding_box, BoundingBox::cell(3, 3));
+    }
+
+    #[test]
+    fn streaming_matches_two_pass() {
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+        for connectivity in [Connectivity::Four, Connectivity::Eight] {
+            let grid: Vec<Vec<bool>> = (0..60)
+                .map(|_| (0..40).map(|_| rng.gen_bool(0.45)).collect())
+                .collect();
+
+            let mut labeler = StreamingLabeler::new(40, connectivity);
+            let mut streamed = Vec::new();
+            for row in &grid {
+                streamed.extend(labeler.push_row(row));
+            }
+            streamed.extend(labeler.finish());
+
+            let mut expected: Vec<(usize, BoundingBox)> = label_grid(&grid, connectivity)
+                .regions
+                .iter()
+                .map(|region| (region.area, region.bounding_box))
+                .collect();
+            let mut actual: Vec<(usize, BoundingBox)> = streamed
+                .iter()
+                .map(|region| (region.area, region.bounding_box))
+                .collect();
+            let key = |entry: &(usize, BoundingBox)| {
+                let (area, bounding_box) = entry;
+                (
+                    bounding_box.min_row,
+                    bounding_box.min_col,
+                    bounding_box.max_row,
+                    bounding_box.max_col,
+                    *area,
+                )
+            };
+            expected.sort_by_key(key);
+            actual.sort_by_key(key);
+            assert_eq!(actual, expected);
+        }
+    }
+}
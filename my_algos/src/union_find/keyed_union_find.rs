@@ -0,0 +1,212 @@
+//! Union-Find over arbitrary hashable keys (strings, UUIDs, (x, y) tuples ...)
+//! instead of dense `usize` vertices.
+//!
+//! Every key is given the next free index the first time it is seen by `union` or `find`,
+//! the actual work is done by `UnionFind` on those indices,
+//! so path compression and union by rank carry over unchanged.
+//!
+//! Each key is stored once, in `keys` at its index. Looking a key up goes through an open
+//! addressing table with linear probing that holds indices only. Keys are never removed,
+//! so the table needs no tombstones, it is doubled once it gets half full.
+//! Like `HashMap`, lookups take any borrowed form of the key, e.g. `&str` for `String` keys.
+
+use super::UnionFind;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+// Marks a free slot of the table
+const EMPTY: usize = usize::MAX;
+const INITIAL_SLOTS: usize = 8;
+
+pub struct KeyedUnionFind<K> {
+    sets: UnionFind,
+    // index -> key
+    keys: Vec<K>,
+    // hash -> index, each slot is `EMPTY` or an index into `keys`
+    slots: Vec<usize>,
+    hasher: RandomState,
+}
+
+impl<K: Hash + Eq> Default for KeyedUnionFind<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq> KeyedUnionFind<K> {
+    pub fn new() -> Self {
+        Self {
+            sets: UnionFind::new(0),
+            keys: Vec::new(),
+            slots: vec![EMPTY; INITIAL_SLOTS],
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Returns the key representing the set of `key`, creating a singleton set if it is new
+    pub fn find<Q>(&mut self, key: &Q) -> &K
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let index = self.index_of(key);
+        let root = self.sets.find(index);
+        &self.keys[root]
+    }
+
+    pub fn union<Q>(&mut self, key_1: &Q, key_2: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let index_1 = self.index_of(key_1);
+        let index_2 = self.index_of(key_2);
+        self.sets.union(index_1, index_2);
+    }
+
+    /// Unknown keys are only connected to themselves, they are not added
+    pub fn is_connected<Q>(&mut self, key_1: &Q, key_2: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match (self.slot(key_1), self.slot(key_2)) {
+            (Ok(index_1), Ok(index_2)) => self.sets.is_connected(index_1, index_2),
+            _ => key_1 == key_2,
+        }
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.slot(key).is_ok()
+    }
+
+    /// Number of keys seen so far
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn get_number_of_disjoint_sets(&self) -> usize {
+        self.sets.get_number_of_disjoint_sets()
+    }
+
+    fn index_of<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let slot = match self.slot(key) {
+            Ok(index) => return index,
+            Err(slot) => slot,
+        };
+
+        let index = self.sets.add_vertex();
+        self.keys.push(key.to_owned());
+        self.slots[slot] = index;
+        if 2 * self.keys.len() > self.slots.len() {
+            self.grow();
+        }
+        index
+    }
+
+    /// `Ok` with the index of `key`, or `Err` with the free slot where it would go
+    fn slot<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mask = self.slots.len() - 1;
+        let mut slot = self.hasher.hash_one(key) as usize & mask;
+        loop {
+            match self.slots[slot] {
+                EMPTY => return Err(slot),
+                index if self.keys[index].borrow() == key => return Ok(index),
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    fn grow(&mut self) {
+        self.slots = vec![EMPTY; 2 * self.slots.len()];
+        for index in 0..self.keys.len() {
+            // Keys are distinct, so this always ends on a free slot
+            if let Err(slot) = self.slot::<K>(&self.keys[index]) {
+                self.slots[slot] = index;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initialize() {
+        let mut disjoint_set: KeyedUnionFind<&str> = KeyedUnionFind::new();
+        assert!(disjoint_set.is_empty());
+        assert!(disjoint_set.is_connected(&"alice", &"alice"));
+        assert!(!disjoint_set.contains(&"alice"));
+
+        assert_eq!(*disjoint_set.find(&"alice"), "alice");
+        assert_eq!(disjoint_set.len(), 1);
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 1);
+    }
+
+    #[test]
+    fn union_strings() {
+        // String keys, looked up by &str
+        let mut disjoint_set: KeyedUnionFind<String> = KeyedUnionFind::new();
+        for (from, to) in [("a", "b"), ("b", "c"), ("x", "y")] {
+            disjoint_set.union(from, to);
+        }
+
+        assert!(disjoint_set.is_connected("a", "c"));
+        assert!(!disjoint_set.is_connected("a", "x"));
+        assert!(!disjoint_set.is_connected("a", "unknown"));
+        assert!(disjoint_set.contains("y"));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 2);
+
+        let root = disjoint_set.find("c").clone();
+        assert_eq!(disjoint_set.find("a"), &root);
+    }
+
+    #[test]
+    fn union_grid_points() {
+        let mut disjoint_set = KeyedUnionFind::new();
+        // Two diagonal lines of points on a grid
+        for step in 0..10 {
+            disjoint_set.union(&(step, step), &(step + 1, step + 1));
+            disjoint_set.union(&(step, -step), &(step + 1, -step - 1));
+        }
+
+        assert!(disjoint_set.is_connected(&(0, 0), &(10, 10)));
+        assert!(disjoint_set.is_connected(&(10, 10), &(10, -10)));
+        assert_eq!(disjoint_set.len(), 21);
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 1);
+    }
+
+    #[test]
+    fn many_keys() {
+        // Enough keys to grow the table several times, every key must still be found
+        let mut disjoint_set: KeyedUnionFind<String> = KeyedUnionFind::new();
+        for value in 0..10_000 {
+            disjoint_set.union(&value.to_string(), &(value % 7).to_string());
+        }
+
+        assert_eq!(disjoint_set.len(), 10_000);
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 7);
+        for value in 0..10_000 {
+            assert!(disjoint_set.is_connected(&value.to_string(), &(value % 7).to_string()));
+        }
+        assert!(!disjoint_set.contains("10000"));
+    }
+}
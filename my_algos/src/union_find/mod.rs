@@ -0,0 +1,375 @@
+pub mod concurrent_union_find;
+pub mod grid_labeling;
+pub mod keyed_union_find;
+pub mod rollback_union_find;
+pub mod weighted_union_find;
+
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnionFindError {
+    VertexOutOfRange { vertex: usize, nf_vertices: usize },
+}
+
+impl fmt::Display for UnionFindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnionFindError::VertexOutOfRange {
+                vertex,
+                nf_vertices,
+            } => write!(
+                f,
+                "vertex {} is out of range for {} vertices",
+                vertex, nf_vertices
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnionFindError {}
+
+/// How `union` decides which root becomes the parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnionStrategy {
+    /// The root of the taller tree wins, rank is an upper bound on the height
+    #[default]
+    Rank,
+    /// The root of the set with more members wins
+    Size,
+}
+
+/// `find`, `union` and `is_connected` panic on vertices that were never added,
+/// use `try_find` / `try_union` when the input is not trusted.
+///
+/// Besides the parent links every set keeps its size at its root and its members linked
+/// in a circular list (`next`), so sizes are O(1) and listing a set is O(size of the set).
+pub struct UnionFind {
+    root: Vec<usize>,
+    rank: Vec<usize>,
+    size: Vec<usize>,
+    next: Vec<usize>,
+    strategy: UnionStrategy,
+    nf_disjoint_sets: usize
+}
+
+impl UnionFind {
+    pub fn new(nf_vertices: usize) -> Self {
+        Self::with_strategy(nf_vertices, UnionStrategy::Rank)
+    }
+
+    pub fn with_strategy(nf_vertices: usize, strategy: UnionStrategy) -> Self {
+        Self {
+            root: (0..nf_vertices).collect(),
+            rank: vec![0; nf_vertices],
+            size: vec![1; nf_vertices],
+            next: (0..nf_vertices).collect(),
+            strategy,
+            nf_disjoint_sets: nf_vertices
+        }
+    }
+
+    /// Returns the root of the vertex, not necessarily the parent
+    /// Compresses the path by halving: every other vertex on the way up is pointed at its
+    /// grandparent. Iterative, so even a very deep chain cannot overflow the stack.
+    pub fn find(&mut self, vertex: usize) -> usize {
+        let mut vertex = vertex;
+        while self.root[vertex] != vertex {
+            self.root[vertex] = self.root[self.root[vertex]];
+            vertex = self.root[vertex];
+        }
+
+        vertex
+    }
+
+    pub fn try_find(&mut self, vertex: usize) -> Result<usize, UnionFindError> {
+        self.check_vertex(vertex)?;
+        Ok(self.find(vertex))
+    }
+
+    /// Performs quick union
+    /// Additional optimization is union by rank or by size, see `UnionStrategy`
+    pub fn union(&mut self, vertex_1: usize, vertex_2: usize) {
+        let root_1 = self.find(vertex_1);
+        let root_2 = self.find(vertex_2);
+
+        if root_1 != root_2 {
+            // select the new root based on the rank or size
+            let (parent, child) = match self.strategy {
+                UnionStrategy::Rank => match (self.rank[root_1]).cmp(&self.rank[root_2]) {
+                    Ordering::Equal => {
+                        self.rank[root_1] += 1;
+                        (root_1, root_2)
+                    },
+                    Ordering::Greater => (root_1, root_2),
+                    Ordering::Less => (root_2, root_1),
+                },
+                UnionStrategy::Size => {
+                    if self.size[root_1] >= self.size[root_2] {
+                        (root_1, root_2)
+                    } else {
+                        (root_2, root_1)
+                    }
+                },
+            };
+
+            self.root[child] = parent;
+            self.size[parent] += self.size[child];
+            // Swapping the successors of two circular lists splices them into one
+            self.next.swap(root_1, root_2);
+            self.nf_disjoint_sets -= 1;
+        }
+    }
+
+    pub fn try_union(&mut self, vertex_1: usize, vertex_2: usize) -> Result<(), UnionFindError> {
+        self.check_vertex(vertex_1)?;
+        self.check_vertex(vertex_2)?;
+        self.union(vertex_1, vertex_2);
+        Ok(())
+    }
+
+    pub fn is_connected(&mut self, vertex_1: usize, vertex_2: usize) -> bool {
+        self.find(vertex_1) == self.find(vertex_2)
+    }
+
+    pub fn get_number_of_disjoint_sets(&self) -> usize {
+        self.nf_disjoint_sets
+    }
+
+    /// Number of vertices in the set containing `vertex`
+    pub fn component_size(&mut self, vertex: usize) -> usize {
+        let root = self.find(vertex);
+        self.size[root]
+    }
+
+    /// Iterates over every vertex in the same set as `vertex`, starting with `vertex` itself
+    pub fn members(&self, vertex: usize) -> Members<'_> {
+        Members {
+            next: &self.next,
+            start: vertex,
+            current: Some(vertex),
+        }
+    }
+
+    /// One representative per set, in increasing order
+    pub fn roots(&self) -> Vec<usize> {
+        (0..self.len()).filter(|&vertex| self.root[vertex] == vertex).collect()
+    }
+
+    /// Members of every set, each sorted, the sets ordered by their smallest member
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut components: Vec<Vec<usize>> = self
+            .roots()
+            .into_iter()
+            .map(|root| {
+                let mut members: Vec<usize> = self.members(root).collect();
+                members.sort_unstable();
+                members
+            })
+            .collect();
+        components.sort_unstable_by_key(|members| members[0]);
+
+        components
+    }
+
+    /// Sorted members of the biggest set, the one with the smallest member on ties.
+    /// `None` if there are no vertices.
+    pub fn largest_component(&self) -> Option<Vec<usize>> {
+        // max_by_key keeps the last maximum, reversed that is the set with the smallest member
+        self.components()
+            .into_iter()
+            .rev()
+            .max_by_key(|members| members.len())
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty()
+    }
+
+    /// Appends a new singleton vertex and returns its index
+    pub fn add_vertex(&mut self) -> usize {
+        let vertex = self.root.len();
+        self.root.push(vertex);
+        self.rank.push(0);
+        self.size.push(1);
+        self.next.push(vertex);
+        self.nf_disjoint_sets += 1;
+
+        vertex
+    }
+
+    /// Makes room for `additional` more vertices without reallocating
+    pub fn reserve(&mut self, additional: usize) {
+        self.root.reserve(additional);
+        self.rank.reserve(additional);
+        self.size.reserve(additional);
+        self.next.reserve(additional);
+    }
+
+    fn check_vertex(&self, vertex: usize) -> Result<(), UnionFindError> {
+        if vertex < self.len() {
+            Ok(())
+        } else {
+            Err(UnionFindError::VertexOutOfRange {
+                vertex,
+                nf_vertices: self.len(),
+            })
+        }
+    }
+
+}
+
+/// Walks the circular member list of one set
+pub struct Members<'a> {
+    next: &'a [usize],
+    start: usize,
+    current: Option<usize>,
+}
+
+impl Iterator for Members<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let vertex = self.current?;
+        let successor = self.next[vertex];
+        self.current = if successor == self.start { None } else { Some(successor) };
+
+        Some(vertex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;    
+
+    #[test]
+    fn initialize() {
+        let mut disjoint_set = UnionFind::new(1);
+        assert!(disjoint_set.is_connected(0, 0));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 1);
+    }
+
+    /// This allows creation of disjoint set from a list of edges
+    fn disjoint_set_from_vec(nf_vertices: usize, edges: Vec<Vec<usize>>) -> UnionFind {
+        let mut disjoint_set = UnionFind::new(nf_vertices);
+
+        for edge in edges {
+            disjoint_set.union(edge[0], edge[1]);
+        }
+
+        disjoint_set
+    }
+
+    #[test]
+    fn create_from_list() {
+        let edges = vec![ vec![0, 1], vec![0, 2], vec![1, 6], vec![6, 7], vec![3, 4]];
+
+        let mut disjoint_set = disjoint_set_from_vec(8, edges);
+
+        assert!(disjoint_set.is_connected(2, 7));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 3);
+    }
+
+    #[test]
+    fn join() {
+        let edges = vec![ vec![0, 1], vec![0, 2], vec![1, 6], vec![6, 7], vec![3, 4]];
+
+        let mut disjoint_set = disjoint_set_from_vec(8, edges);
+        
+        // Add a new edge between 2, 3, that merges the two disjoint sets
+        disjoint_set.union(2, 3);
+
+        assert!(disjoint_set.is_connected(4, 7));
+        assert!(!disjoint_set.is_connected(5, 7));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 2);
+    }
+
+    #[test]
+    fn try_out_of_range() {
+        let mut disjoint_set = UnionFind::new(3);
+
+        assert_eq!(disjoint_set.try_find(2), Ok(2));
+        assert_eq!(
+            disjoint_set.try_find(3),
+            Err(UnionFindError::VertexOutOfRange { vertex: 3, nf_vertices: 3 })
+        );
+        assert!(disjoint_set.try_union(0, 7).is_err());
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 3);
+
+        assert_eq!(disjoint_set.try_union(0, 2), Ok(()));
+        assert!(disjoint_set.is_connected(0, 2));
+    }
+
+    #[test]
+    fn find_deep_chain() {
+        // Union by rank never builds a chain like this, but a recursive find would overflow on it
+        let nf_vertices = 1_000_000;
+        let mut disjoint_set = UnionFind::new(nf_vertices);
+        for vertex in 0..nf_vertices - 1 {
+            disjoint_set.root[vertex] = vertex + 1;
+        }
+
+        assert_eq!(disjoint_set.find(0), nf_vertices - 1);
+        // Path halving at least halved the path from 0
+        assert!(disjoint_set.root[0] >= 2);
+    }
+
+    #[test]
+    fn grow() {
+        let mut disjoint_set = UnionFind::new(2);
+        disjoint_set.reserve(10);
+
+        let vertex = disjoint_set.add_vertex();
+        assert_eq!(vertex, 2);
+        assert_eq!(disjoint_set.len(), 3);
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 3);
+
+        disjoint_set.union(0, vertex);
+        assert!(disjoint_set.is_connected(0, 2));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 2);
+    }
+
+    #[test]
+    fn component_queries() {
+        let edges = vec![ vec![0, 1], vec![0, 2], vec![1, 6], vec![6, 7], vec![3, 4]];
+
+        let mut disjoint_set = disjoint_set_from_vec(8, edges);
+
+        assert_eq!(disjoint_set.component_size(7), 5);
+        assert_eq!(disjoint_set.component_size(4), 2);
+        assert_eq!(disjoint_set.component_size(5), 1);
+        assert_eq!(disjoint_set.roots().len(), 3);
+        assert_eq!(disjoint_set.components(), vec![vec![0, 1, 2, 6, 7], vec![3, 4], vec![5]]);
+        assert_eq!(disjoint_set.largest_component(), Some(vec![0, 1, 2, 6, 7]));
+
+        let mut members: Vec<usize> = disjoint_set.members(6).collect();
+        assert_eq!(members[0], 6);
+        members.sort();
+        assert_eq!(members, vec![0, 1, 2, 6, 7]);
+        assert_eq!(disjoint_set.members(5).collect::<Vec<_>>(), vec![5]);
+
+        assert_eq!(UnionFind::new(0).largest_component(), None);
+    }
+
+    #[test]
+    fn union_by_size() {
+        let mut disjoint_set = UnionFind::with_strategy(6, UnionStrategy::Size);
+        disjoint_set.union(0, 1);
+        disjoint_set.union(0, 2);
+        disjoint_set.union(3, 4);
+
+        // The bigger set {0, 1, 2} keeps its root
+        let big_root = disjoint_set.find(0);
+        disjoint_set.union(4, 0);
+        assert_eq!(disjoint_set.find(3), big_root);
+        assert_eq!(disjoint_set.component_size(3), 5);
+
+        disjoint_set.add_vertex();
+        disjoint_set.union(6, 5);
+        assert_eq!(disjoint_set.components(), vec![vec![0, 1, 2, 3, 4], vec![5, 6]]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Union-Find that can undo unions, the building block for offline dynamic connectivity
+//! (segment tree over time) and backtracking searches.
+//!
+//! Path compression would rewrite links all over the tree and make undoing expensive,
+//! so this variant only uses union by rank. That keeps trees O(log n) deep,
+//! `find` stays O(log n) and never mutates, and every union changes exactly one link
+//! (plus maybe one rank), which is what goes on the history stack.
+//! Rolling back pops those records, O(1) per undone union.
+
+use std::cmp::Ordering;
+
+/// A point in the history to roll back to, see `RollbackUnionFind::snapshot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot(usize);
+
+/// What a single successful union changed
+struct Change {
+    child: usize,
+    parent: usize,
+    rank_increased: bool,
+}
+
+pub struct RollbackUnionFind {
+    root: Vec<usize>,
+    rank: Vec<usize>,
+    history: Vec<Change>,
+    nf_disjoint_sets: usize,
+}
+
+impl RollbackUnionFind {
+    pub fn new(nf_vertices: usize) -> Self {
+        Self {
+            root: (0..nf_vertices).collect(),
+            rank: vec![0; nf_vertices],
+            history: Vec::new(),
+            nf_disjoint_sets: nf_vertices,
+        }
+    }
+
+    /// Returns the root of the vertex, without compressing the path
+    pub fn find(&self, vertex: usize) -> usize {
+        let mut vertex = vertex;
+        while self.root[vertex] != vertex {
+            vertex = self.root[vertex];
+        }
+
+        vertex
+    }
+
+    /// Union by rank, returns whether two sets were actually merged.
+    /// Only merges are recorded, so undoing never has to skip no-ops.
+    pub fn union(&mut self, vertex_1: usize, vertex_2: usize) -> bool {
+        let root_1 = self.find(vertex_1);
+        let root_2 = self.find(vertex_2);
+        if root_1 == root_2 {
+            return false;
+        }
+
+        let (parent, child) = match self.rank[root_1].cmp(&self.rank[root_2]) {
+            Ordering::Less => (root_2, root_1),
+            _ => (root_1, root_2),
+        };
+        let rank_increased = self.rank[parent] == self.rank[child];
+
+        self.root[child] = parent;
+        if rank_increased {
+            self.rank[parent] += 1;
+        }
+        self.nf_disjoint_sets -= 1;
+        self.history.push(Change {
+            child,
+            parent,
+            rank_increased,
+        });
+
+        true
+    }
+
+    pub fn is_connected(&self, vertex_1: usize, vertex_2: usize) -> bool {
+        self.find(vertex_1) == self.find(vertex_2)
+    }
+
+    pub fn get_number_of_disjoint_sets(&self) -> usize {
+        self.nf_disjoint_sets
+    }
+
+    /// Remembers the current state, hand it to `rollback` to get back here
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.history.len())
+    }
+
+    /// Undoes every union made since `snapshot` was taken.
+    ///
+    /// Panics if the snapshot is newer than the current state,
+    /// i.e. it was taken after an earlier rollback already went past it.
+    pub fn rollback(&mut self, snapshot: Snapshot) {
+        assert!(
+            snapshot.0 <= self.history.len(),
+            "cannot roll back to a snapshot taken after the current state"
+        );
+
+        while self.history.len() > snapshot.0 {
+            self.undo();
+        }
+    }
+
+    /// Undoes the most recent merge, returns false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(change) = self.history.pop() else {
+            return false;
+        };
+
+        self.root[change.child] = change.child;
+        if change.rank_increased {
+            self.rank[change.parent] -= 1;
+        }
+        self.nf_disjoint_sets += 1;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_and_rollback() {
+        let mut disjoint_set = RollbackUnionFind::new(6);
+        disjoint_set.union(0, 1);
+        let before = disjoint_set.snapshot();
+
+        assert!(disjoint_set.union(1, 2));
+        assert!(disjoint_set.union(3, 4));
+        assert!(!disjoint_set.union(0, 2));
+        assert!(disjoint_set.is_connected(0, 2));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 3);
+
+        disjoint_set.rollback(before);
+        assert!(disjoint_set.is_connected(0, 1));
+        assert!(!disjoint_set.is_connected(0, 2));
+        assert!(!disjoint_set.is_connected(3, 4));
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 5);
+    }
+
+    #[test]
+    fn nested_snapshots_restore_ranks() {
+        let mut disjoint_set = RollbackUnionFind::new(8);
+        let empty = disjoint_set.snapshot();
+
+        disjoint_set.union(0, 1);
+        disjoint_set.union(2, 3);
+        let pairs = disjoint_set.snapshot();
+        disjoint_set.union(0, 2);
+        disjoint_set.union(4, 0);
+
+        disjoint_set.rollback(pairs);
+        assert_eq!(disjoint_set.rank, vec![1, 0, 1, 0, 0, 0, 0, 0]);
+        assert!(!disjoint_set.is_connected(1, 3));
+
+        disjoint_set.rollback(empty);
+        assert_eq!(disjoint_set.rank, vec![0; 8]);
+        assert_eq!(disjoint_set.root, (0..8).collect::<Vec<_>>());
+        assert!(!disjoint_set.undo());
+    }
+
+    /// Backtracking search: count the ways to pick one edge out of each group
+    /// so that the picked edges connect all four vertices
+    #[test]
+    fn backtracking_search() {
+        fn count(disjoint_set: &mut RollbackUnionFind, groups: &[Vec<(usize, usize)>]) -> usize {
+            let Some((group, rest)) = groups.split_first() else {
+                return (disjoint_set.get_number_of_disjoint_sets() == 1) as usize;
+            };
+
+            let mut total = 0;
+            for &(from, to) in group {
+                let snapshot = disjoint_set.snapshot();
+                disjoint_set.union(from, to);
+                total += count(disjoint_set, rest);
+                disjoint_set.rollback(snapshot);
+            }
+            total
+        }
+
+        let groups = vec![
+            vec![(0, 1), (0, 2)],
+            vec![(1, 2), (2, 3)],
+            vec![(1, 3), (0, 3)],
+        ];
+        let mut disjoint_set = RollbackUnionFind::new(4);
+
+        // Picks forming a spanning tree: every combination except a triangle
+        assert_eq!(count(&mut disjoint_set, &groups), 7);
+        assert_eq!(disjoint_set.get_number_of_disjoint_sets(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rollback_to_future_snapshot() {
+        let mut disjoint_set = RollbackUnionFind::new(2);
+        disjoint_set.union(0, 1);
+        let after = disjoint_set.snapshot();
+        disjoint_set.undo();
+        disjoint_set.rollback(after);
+    }
+}
@@ -0,0 +1,293 @@
+//! Union-Find where every vertex also carries a potential relative to the root of its set.
+//!
+//! `union(a, b, w)` records "b is w relative to a", e.g. `val(b) - val(a) = w` for offsets
+//! like clock skew between hosts or `val(b) / val(a) = w` for ratios like exchange rates.
+//! `diff(a, b)` then answers that relation for any two vertices of the same set,
+//! and a `union` that disagrees with what is already known is reported as a `Contradiction`.
+//!
+//! `weight[v]` is the potential of `v` relative to its parent. `find` sums the weights on the
+//! way up and compresses the path, so afterwards `weight[v]` is relative to the root directly.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// How potentials are combined, this has to be a commutative group:
+/// additive for offsets, multiplicative for ratios.
+pub trait Potential: Copy {
+    /// The potential of a vertex relative to itself
+    fn identity() -> Self;
+
+    /// Potential of c relative to a, given b relative to a (`self`) and c relative to b
+    fn compose(self, other: Self) -> Self;
+
+    /// a relative to b, given b relative to a
+    fn inverse(self) -> Self;
+
+    /// Whether two potentials for the same pair of vertices agree
+    fn agrees_with(self, other: Self) -> bool;
+}
+
+macro_rules! impl_potential_for_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl Potential for $ty {
+                fn identity() -> Self {
+                    0
+                }
+
+                fn compose(self, other: Self) -> Self {
+                    self + other
+                }
+
+                fn inverse(self) -> Self {
+                    -self
+                }
+
+                fn agrees_with(self, other: Self) -> bool {
+                    self == other
+                }
+            }
+        )*
+    };
+}
+
+impl_potential_for_integer!(i32, i64, i128);
+
+// Relative error we still accept as the same floating point potential
+const FLOAT_TOLERANCE: f64 = 1e-9;
+
+/// Additive offsets, compared with a small tolerance
+impl Potential for f64 {
+    fn identity() -> Self {
+        0.0
+    }
+
+    fn compose(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn inverse(self) -> Self {
+        -self
+    }
+
+    fn agrees_with(self, other: Self) -> bool {
+        (self - other).abs() <= FLOAT_TOLERANCE * self.abs().max(other.abs()).max(1.0)
+    }
+}
+
+/// Multiplicative potential, `union(a, b, Ratio(r))` records `val(b) / val(a) = r`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ratio(pub f64);
+
+impl Potential for Ratio {
+    fn identity() -> Self {
+        Ratio(1.0)
+    }
+
+    fn compose(self, other: Self) -> Self {
+        Ratio(self.0 * other.0)
+    }
+
+    fn inverse(self) -> Self {
+        Ratio(1.0 / self.0)
+    }
+
+    fn agrees_with(self, other: Self) -> bool {
+        (self.0 - other.0).abs() <= FLOAT_TOLERANCE * self.0.abs().max(other.0.abs())
+    }
+}
+
+/// A `union` disagreed with the relation already implied by earlier unions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contradiction<W> {
+    pub existing: W,
+    pub requested: W,
+}
+
+impl<W: fmt::Debug> fmt::Display for Contradiction<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "union requested {:?} but earlier unions imply {:?}",
+            self.requested, self.existing
+        )
+    }
+}
+
+impl<W: fmt::Debug> std::error::Error for Contradiction<W> {}
+
+pub struct WeightedUnionFind<W> {
+    root: Vec<usize>,
+    rank: Vec<usize>,
+    weight: Vec<W>,
+    nf_disjoint_sets: usize,
+}
+
+impl<W: Potential> WeightedUnionFind<W> {
+    pub fn new(nf_vertices: usize) -> Self {
+        Self {
+            root: (0..nf_vertices).collect(),
+            rank: vec![0; nf_vertices],
+            weight: vec![W::identity(); nf_vertices],
+            nf_disjoint_sets: nf_vertices,
+        }
+    }
+
+    /// Returns the root of the vertex and the potential of the vertex relative to that root
+    pub fn find(&mut self, vertex: usize) -> (usize, W) {
+        let mut path = Vec::new();
+        let mut root = vertex;
+        while self.root[root] != root {
+            path.push(root);
+            root = self.root[root];
+        }
+
+        // Fix the path from the top down, so every parent is already relative to the root
+        for &node in path.iter().rev() {
+            let parent = self.root[node];
+            if parent != root {
+                self.weight[node] = self.weight[parent].compose(self.weight[node]);
+                self.root[node] = root;
+            }
+        }
+
+        (root, self.weight[vertex])
+    }
+
+    /// Records that `vertex_2` is `weight` relative to `vertex_1`.
+    /// If both are already connected the relation is only checked.
+    pub fn union(
+        &mut self,
+        vertex_1: usize,
+        vertex_2: usize,
+        weight: W,
+    ) -> Result<(), Contradiction<W>> {
+        let (root_1, potential_1) = self.find(vertex_1);
+        let (root_2, potential_2) = self.find(vertex_2);
+
+        if root_1 == root_2 {
+            let existing = potential_1.inverse().compose(potential_2);
+            if existing.agrees_with(weight) {
+                return Ok(());
+            }
+            return Err(Contradiction {
+                existing,
+                requested: weight,
+            });
+        }
+
+        // root_2 relative to root_1
+        let root_weight = potential_1.compose(weight).compose(potential_2.inverse());
+
+        match self.rank[root_1].cmp(&self.rank[root_2]) {
+            Ordering::Less => {
+                self.root[root_1] = root_2;
+                self.weight[root_1] = root_weight.inverse();
+            }
+            ordering => {
+                self.root[root_2] = root_1;
+                self.weight[root_2] = root_weight;
+                if ordering == Ordering::Equal {
+                    self.rank[root_1] += 1;
+                }
+            }
+        }
+
+        self.nf_disjoint_sets -= 1;
+        Ok(())
+    }
+
+    /// `vertex_2` relative to `vertex_1`, `None` if they are not connected
+    pub fn diff(&mut self, vertex_1: usize, vertex_2: usize) -> Option<W> {
+        let (root_1, potential_1) = self.find(vertex_1);
+        let (root_2, potential_2) = self.find(vertex_2);
+
+        (root_1 == root_2).then(|| potential_1.inverse().compose(potential_2))
+    }
+
+    pub fn is_connected(&mut self, vertex_1: usize, vertex_2: usize) -> bool {
+        self.find(vertex_1).0 == self.find(vertex_2).0
+    }
+
+    pub fn get_number_of_disjoint_sets(&self) -> usize {
+        self.nf_disjoint_sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_skew() {
+        // Host 1 runs 5ms ahead of host 0, host 2 runs 3ms behind host 1 ...
+        let mut skew: WeightedUnionFind<i64> = WeightedUnionFind::new(5);
+        skew.union(0, 1, 5).unwrap();
+        skew.union(1, 2, -3).unwrap();
+        skew.union(3, 2, 10).unwrap();
+
+        assert_eq!(skew.diff(0, 2), Some(2));
+        assert_eq!(skew.diff(2, 0), Some(-2));
+        assert_eq!(skew.diff(0, 3), Some(-8));
+        assert_eq!(skew.diff(4, 4), Some(0));
+        assert_eq!(skew.diff(0, 4), None);
+        assert_eq!(skew.get_number_of_disjoint_sets(), 2);
+    }
+
+    #[test]
+    fn contradiction() {
+        let mut skew: WeightedUnionFind<i64> = WeightedUnionFind::new(3);
+        skew.union(0, 1, 5).unwrap();
+        skew.union(1, 2, 5).unwrap();
+
+        assert_eq!(skew.union(0, 2, 10), Ok(()));
+        assert_eq!(
+            skew.union(2, 0, 7),
+            Err(Contradiction {
+                existing: -10,
+                requested: 7
+            })
+        );
+        assert_eq!(skew.get_number_of_disjoint_sets(), 1);
+    }
+
+    #[test]
+    fn long_chain_compresses() {
+        let nf_vertices = 10_000;
+        let mut offsets: WeightedUnionFind<i64> = WeightedUnionFind::new(nf_vertices);
+        for vertex in 1..nf_vertices {
+            offsets.union(vertex - 1, vertex, 1).unwrap();
+        }
+
+        assert_eq!(
+            offsets.diff(0, nf_vertices - 1),
+            Some(nf_vertices as i64 - 1)
+        );
+        assert_eq!(offsets.diff(7000, 1234), Some(1234 - 7000));
+    }
+
+    #[test]
+    fn currency_conversion() {
+        const USD: usize = 0;
+        const EUR: usize = 1;
+        const JPY: usize = 2;
+        const GBP: usize = 3;
+
+        // `union(a, b, Ratio(r))`: one unit of a buys r units of b
+        let mut rates = WeightedUnionFind::new(4);
+        rates.union(USD, EUR, Ratio(0.9)).unwrap();
+        rates.union(EUR, JPY, Ratio(160.0)).unwrap();
+
+        let usd_to_jpy = rates.diff(USD, JPY).unwrap();
+        assert!(usd_to_jpy.agrees_with(Ratio(144.0)));
+        assert!(rates
+            .diff(JPY, USD)
+            .unwrap()
+            .agrees_with(Ratio(1.0 / 144.0)));
+        assert_eq!(rates.diff(USD, GBP), None);
+
+        // Consistent up to rounding is fine, an arbitrage loop is not
+        assert!(rates.union(USD, JPY, Ratio(0.9 * 160.0)).is_ok());
+        assert!(rates.union(JPY, USD, Ratio(0.01)).is_err());
+    }
+}